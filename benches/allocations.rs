@@ -0,0 +1,139 @@
+//! Counts the heap allocations made while bullets are shot and removed.
+//!
+//! Compares the old storage, where every boss volley was collected in a new
+//! `Vec` and dead bullets were dropped with `Vec::retain`, with `Pool`. Run
+//! it with `cargo bench --bench allocations`.
+
+extern crate hit_n_run;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use hit_n_run::config::Config;
+use hit_n_run::models::boss::Boss;
+use hit_n_run::pool::Pool;
+use hit_n_run::rng::GameRng;
+use hit_n_run::script::Script;
+use hit_n_run::simulation::{Input, Simulation};
+use hit_n_run::weapons::bullet::Bullet;
+
+/// Allocator that counts allocations and reallocations
+struct Counter;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counter {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counter = Counter;
+
+/// Frames simulated by every scenario
+const FRAMES: usize = 10_000;
+/// Length of a frame in seconds
+const DT: f64 = 1.0 / 60.0;
+/// Size of the screen bullets fly around in
+const DIMENSIONS: [f64; 2] = [600.0, 600.0];
+
+/// Returns the allocations made while running `f`
+fn count<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    f();
+    ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+/// Returns a config with big boss volleys
+fn bullet_hell() -> Config {
+    let mut config = Config::default();
+    config.boss.volley_size = 32;
+    config
+}
+
+/// Bosses shooting a volley every frame the way they did before pooling:
+/// every volley is a new `Vec` that is appended to the rest
+fn vec_volleys(config: &Config) {
+    let mut rng = GameRng::new(1);
+    let bosses: Vec<Boss> = (0..4).map(|_| Boss::new(300.0, 300.0, config, &mut rng)).collect();
+    let mut bullets: Vec<Bullet> = Vec::new();
+
+    for _ in 0..FRAMES {
+        for boss in &bosses {
+            let mut volley = Vec::new();
+            for gun in 0..config.boss.volley_size {
+                let rot = boss.get_gun_rotation(gun, config);
+                volley.push(Bullet::new(boss.get_x(), boss.get_y(), rot, true));
+            }
+            bullets.append(&mut volley);
+        }
+        for bullet in &mut bullets {
+            bullet.update(DT, &DIMENSIONS, config);
+        }
+        bullets.retain(|b| b.get_alive());
+    }
+}
+
+/// Bosses shooting a volley every frame straight into a pool
+fn pool_volleys(config: &Config) {
+    let mut rng = GameRng::new(1);
+    let mut bosses: Vec<Boss> = (0..4).map(|_| Boss::new(300.0, 300.0, config, &mut rng)).collect();
+    let mut bullets: Pool<Bullet> = Pool::new();
+
+    for _ in 0..FRAMES {
+        for boss in &mut bosses {
+            boss.shoot_bullets(config, &mut bullets);
+        }
+        for bullet in &mut bullets {
+            bullet.update(DT, &DIMENSIONS, config);
+        }
+        bullets.retain(|b| b.get_alive());
+    }
+}
+
+/// A whole game with the player shooting at the first enemy. Only the
+/// frames after the first second are counted
+fn simulation() -> usize {
+    let mut sim = Simulation::new(DIMENSIONS[0],
+                                  DIMENSIONS[1],
+                                  42,
+                                  Config::default(),
+                                  Script::default());
+    let mut input = Vec::with_capacity(2);
+
+    let mut step = |sim: &mut Simulation| {
+        input.clear();
+        input.push(Input::Shoot(true));
+        if let Some(enemy) = sim.get_enemies().iter().next() {
+            input.push(Input::Target(enemy.get_x(), enemy.get_y() + 100.0));
+        }
+        sim.step(DT, &input);
+    };
+
+    for _ in 0..60 {
+        step(&mut sim);
+    }
+    count(|| for _ in 0..FRAMES {
+        step(&mut sim);
+    })
+}
+
+fn main() {
+    let config = bullet_hell();
+
+    println!("allocations over {} frames", FRAMES);
+    println!("  vec volleys:  {}", count(|| vec_volleys(&config)));
+    println!("  pool volleys: {}", count(|| pool_volleys(&config)));
+    println!("  simulation:   {}", simulation());
+}
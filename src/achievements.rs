@@ -0,0 +1,256 @@
+//! Goals unlocked by playing, driven by the events of the game. Unlocks are
+//! kept between games in the user's data directory.
+//!
+//! The file starts with a header holding its version, followed by a line for
+//! every unlocked achievement with the day it was unlocked. Lines that can't
+//! be read are skipped.
+
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+
+use constants::achievement_constants::*;
+use constants::player_constants::STARTSHOTS;
+use data;
+use events::{Event, Observer};
+use simulation::Simulation;
+
+/// Version of the file format. Files of other versions are not read
+pub const VERSION: u32 = 1;
+
+/// Start of the first line of every achievements file
+const HEADER: &str = "hit_n_run achievements";
+
+/// Something to accomplish in a run
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Achievement {
+    /// Reach `VETERANLEVEL`
+    Veteran,
+    /// Kill a boss on a level without taking damage on it
+    Flawless,
+    /// Clear a level of `MARKSMANKILLS` or more kills without firing more
+    /// than one burst
+    Marksman,
+    /// Stay alive for `SURVIVORTIME` seconds
+    Survivor,
+}
+
+impl Achievement {
+    /// Every achievement, in the order they are listed
+    pub const ALL: [Achievement; 4] = [Achievement::Veteran,
+                                       Achievement::Flawless,
+                                       Achievement::Marksman,
+                                       Achievement::Survivor];
+
+    /// Returns the name of the achievement in the achievements file
+    pub fn get_id(&self) -> &'static str {
+        match *self {
+            Achievement::Veteran => "veteran",
+            Achievement::Flawless => "flawless",
+            Achievement::Marksman => "marksman",
+            Achievement::Survivor => "survivor",
+        }
+    }
+
+    /// Returns the name of the achievement shown to the player
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            Achievement::Veteran => "Veteran",
+            Achievement::Flawless => "Flawless",
+            Achievement::Marksman => "Marksman",
+            Achievement::Survivor => "Survivor",
+        }
+    }
+
+    /// Returns what has to be done to unlock the achievement
+    pub fn get_description(&self) -> String {
+        match *self {
+            Achievement::Veteran => format!("Reach level {}", VETERANLEVEL),
+            Achievement::Flawless => "Kill a boss without taking damage on its level".to_string(),
+            Achievement::Marksman => {
+                format!("Clear a level of {} or more kills with a single burst", MARKSMANKILLS)
+            }
+            Achievement::Survivor => format!("Survive for {} minutes", SURVIVORTIME / 60.0),
+        }
+    }
+}
+
+/// The achievements that were unlocked, and how the current run is doing on
+/// the others
+pub struct Achievements {
+    /// Unlocked achievements and the day they were unlocked on
+    unlocked: Vec<(Achievement, String)>,
+    /// Achievements unlocked since they were last taken
+    new: Vec<Achievement>,
+    /// Shots in a burst, as configured in the last step
+    burst: u32,
+    /// Shots fired on the current level
+    level_shots: u32,
+    /// Health lost on the current level
+    level_damage: u32,
+    /// Enemies and bosses killed on the current level
+    level_kills: u32,
+    /// Whether the current level was played from its start, so everything
+    /// on it was counted
+    level_seen: bool,
+}
+
+impl Default for Achievements {
+    fn default() -> Self {
+        Achievements::new()
+    }
+}
+
+impl Achievements {
+    /// Returns achievements without any unlocked
+    pub fn new() -> Self {
+        Achievements {
+            unlocked: Vec::new(),
+            new: Vec::new(),
+            burst: STARTSHOTS,
+            level_shots: 0,
+            level_damage: 0,
+            level_kills: 0,
+            level_seen: true,
+        }
+    }
+
+    /// Returns where the achievements are kept, in the data directory of the
+    /// user. `None` when the system doesn't have one
+    pub fn default_path() -> Option<PathBuf> {
+        data::get_path(ACHIEVEMENTS)
+    }
+
+    /// Reads the unlocked achievements. A missing file unlocks none. Also
+    /// returns how many lines were skipped because they couldn't be read
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<(Self, usize)> {
+        let mut bytes = Vec::new();
+        match File::open(path) {
+            Ok(mut file) => file.read_to_end(&mut bytes)?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok((Achievements::new(), 0))
+            }
+            Err(e) => return Err(e),
+        };
+        // bytes that aren't text only spoil the lines they are on
+        Ok(Achievements::parse(&String::from_utf8_lossy(&bytes)))
+    }
+
+    /// Reads the unlocked achievements from text. Also returns how many
+    /// lines were skipped because they couldn't be read. A file without the
+    /// header of this version has every line skipped
+    pub fn parse(text: &str) -> (Self, usize) {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let mut achievements = Achievements::new();
+
+        let header = format!("{} {}", HEADER, VERSION);
+        match lines.next() {
+            Some(line) if line.trim() == header => {}
+            Some(_) => return (achievements, 1 + lines.count()),
+            None => return (achievements, 0),
+        }
+
+        let mut skipped = 0;
+        for line in lines {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let found = Achievement::ALL.iter().find(|a| Some(&a.get_id()) == words.first());
+            match (found, words.len()) {
+                (Some(&achievement), 2) if !achievements.get_unlocked(achievement) => {
+                    achievements.unlocked.push((achievement, words[1].to_string()));
+                }
+                _ => skipped += 1,
+            }
+        }
+        (achievements, skipped)
+    }
+
+    /// Writes the unlocked achievements. The old file is only replaced once
+    /// the new one is written in full
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = format!("{} {}\n", HEADER, VERSION);
+        for &(achievement, ref date) in &self.unlocked {
+            out.push_str(&format!("{} {}\n", achievement.get_id(), date));
+        }
+        data::replace_file(path.as_ref(), &out)
+    }
+
+    /// Returns whether an achievement is unlocked
+    pub fn get_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.iter().any(|&(a, _)| a == achievement)
+    }
+
+    /// Returns the day an achievement was unlocked on, like `2017-05-04`
+    pub fn get_date(&self, achievement: Achievement) -> Option<&str> {
+        self.unlocked.iter().find(|&&(a, _)| a == achievement).map(|(_, d)| d.as_str())
+    }
+
+    /// Returns the achievements unlocked since this was last called
+    pub fn take_new(&mut self) -> Vec<Achievement> {
+        self.new.split_off(0)
+    }
+
+    /// Checks the achievements that depend on the state of the game rather
+    /// than on what happened. Called after every step
+    pub fn on_step(&mut self, sim: &Simulation) {
+        self.burst = sim.get_config().player.start_shots;
+        if !sim.get_game_over() && sim.get_run_time() >= SURVIVORTIME {
+            self.unlock(Achievement::Survivor);
+        }
+    }
+
+    /// Forgets the level being played when a saved game is loaded. What
+    /// happened on the loaded level before it was saved wasn't counted, so
+    /// it can't unlock Flawless or Marksman
+    pub fn on_load(&mut self) {
+        self.start_level();
+        self.level_seen = false;
+    }
+
+    /// Starts counting a level from its start
+    fn start_level(&mut self) {
+        self.level_shots = 0;
+        self.level_damage = 0;
+        self.level_kills = 0;
+        self.level_seen = true;
+    }
+
+    /// Unlocks an achievement unless it already is
+    fn unlock(&mut self, achievement: Achievement) {
+        if !self.get_unlocked(achievement) {
+            let date = Local::now().format("%Y-%m-%d").to_string();
+            self.unlocked.push((achievement, date));
+            self.new.push(achievement);
+        }
+    }
+}
+
+impl Observer for Achievements {
+    fn on_event(&mut self, _tick: u64, event: &Event) {
+        match *event {
+            Event::PlayerFired => self.level_shots += 1,
+            Event::PlayerDamaged { .. } => self.level_damage += 1,
+            Event::EnemyKilled { .. } => self.level_kills += 1,
+            Event::BossKilled { .. } => {
+                self.level_kills += 1;
+                if self.level_seen && self.level_damage == 0 {
+                    self.unlock(Achievement::Flawless);
+                }
+            }
+            Event::LevelAdvanced { level } => {
+                if self.level_seen && self.level_shots <= self.burst &&
+                   self.level_kills >= MARKSMANKILLS {
+                    self.unlock(Achievement::Marksman);
+                }
+                if level >= VETERANLEVEL {
+                    self.unlock(Achievement::Veteran);
+                }
+                self.start_level();
+            }
+            Event::GameReset { .. } => self.start_level(),
+            _ => {}
+        }
+    }
+}
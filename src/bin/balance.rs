@@ -0,0 +1,248 @@
+//! Plays many games without a window and prints how they went, so gameplay
+//! values can be tuned with numbers instead of by feel.
+
+extern crate hit_n_run;
+
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
+use hit_n_run::controller::Controller;
+use hit_n_run::controller::bot::{Bot, Strategy};
+use hit_n_run::config::Config;
+use hit_n_run::config::difficulty::Difficulty;
+use hit_n_run::constants::game_constants::TIMESTEP;
+use hit_n_run::constants::sizes;
+use hit_n_run::script::Script;
+use hit_n_run::simulation::Simulation;
+
+/// Options passed on the command line
+struct Options {
+    /// Amount of games to play
+    games: u32,
+    /// Seed of the first game. The others count up from it
+    seed: u64,
+    /// Longest a game is played for, in seconds
+    max_time: f64,
+    /// How the bot plays
+    strategy: Strategy,
+    /// Config file with gameplay values
+    config: Option<PathBuf>,
+    /// Script with the levels of the game
+    levels: Option<PathBuf>,
+    /// Difficulty the config is scaled to
+    difficulty: Difficulty,
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        games: 100,
+        seed: 1,
+        max_time: 600.0,
+        strategy: Strategy::Hunter,
+        config: None,
+        levels: None,
+        difficulty: Difficulty::Normal,
+    };
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => {
+                let value = args.next().ok_or("--games needs a value")?;
+                options.games = value.parse().map_err(|_| format!("invalid games: {}", value))?;
+            }
+            "--seed" => {
+                let value = args.next().ok_or("--seed needs a value")?;
+                options.seed = value.parse().map_err(|_| format!("invalid seed: {}", value))?;
+            }
+            "--max-time" => {
+                let value = args.next().ok_or("--max-time needs a value")?;
+                options.max_time = match value.parse() {
+                    Ok(t) if t > 0.0 => t,
+                    _ => return Err(format!("invalid max time: {}", value)),
+                };
+            }
+            "--bot" => {
+                options.strategy = match args.next().ok_or("--bot needs a value")?.as_str() {
+                    "hunter" => Strategy::Hunter,
+                    "idle" => Strategy::Idle,
+                    other => return Err(format!("unknown bot: {}", other)),
+                };
+            }
+            "--config" => {
+                options.config = Some(PathBuf::from(args.next().ok_or("--config needs a path")?));
+            }
+            "--levels" => {
+                options.levels = Some(PathBuf::from(args.next().ok_or("--levels needs a path")?));
+            }
+            "--difficulty" => {
+                let value = args.next().ok_or("--difficulty needs a value")?;
+                options.difficulty = Difficulty::parse(&value)
+                    .ok_or_else(|| format!("invalid difficulty: {}", value))?;
+            }
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+
+    if options.games == 0 {
+        return Err("--games must be at least 1".to_string());
+    }
+    Ok(options)
+}
+
+/// How a single game went
+struct Stats {
+    /// Level the game ended on
+    level: u32,
+    /// Seconds the player stayed alive
+    survival: f64,
+    score: u32,
+    /// Health lost to bullets
+    bullet_damage: u32,
+    /// Health lost to orbs
+    orb_damage: u32,
+    /// Bullets shot by the player
+    shots: u32,
+    /// Fraction of the shots that hit
+    accuracy: f64,
+    /// Rating of the director when the game ended
+    rating: f64,
+    /// Whether the player was still alive when time ran out
+    survived: bool,
+}
+
+/// Plays a game until the player dies or time runs out
+fn play(seed: u64, options: &Options, config: &Config, script: &Script) -> Stats {
+    let mut sim = Simulation::new(sizes::INITWIDTH as f64,
+                                  sizes::INITHEIGHT as f64,
+                                  seed,
+                                  config.clone(),
+                                  script.clone());
+    let mut bot = Bot::new(options.strategy);
+    let mut input = Vec::new();
+    let max_ticks = (options.max_time / TIMESTEP).ceil() as u64;
+
+    while !sim.get_game_over() && sim.get_tick() < max_ticks {
+        input.clear();
+        bot.inputs(&sim, &mut input);
+        sim.step(TIMESTEP, &input);
+    }
+
+    let run = sim.get_stats();
+    Stats {
+        level: sim.get_level(),
+        survival: sim.get_tick() as f64 * TIMESTEP,
+        score: sim.get_score(),
+        bullet_damage: run.bullet_damage,
+        orb_damage: run.orb_damage,
+        shots: run.shots,
+        accuracy: run.get_accuracy(),
+        rating: sim.get_director().get_rating(),
+        survived: !sim.get_game_over(),
+    }
+}
+
+/// Returns one value of every game
+fn column<F: Fn(&Stats) -> f64>(games: &[Stats], f: F) -> Vec<f64> {
+    games.iter().map(f).collect()
+}
+
+/// Returns the value below which `fraction` of the sorted values are
+fn percentile(sorted: &[f64], fraction: f64) -> f64 {
+    let index = ((sorted.len() - 1) as f64 * fraction).round() as usize;
+    sorted[index]
+}
+
+/// Prints a row with the spread of one value over every game
+fn print_row(name: &str, mut values: Vec<f64>) {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    println!("{:<16}{:>9.1}{:>9.1}{:>9.1}{:>9.1}{:>9.1}{:>9.1}",
+             name,
+             values[0],
+             percentile(&values, 0.1),
+             percentile(&values, 0.5),
+             percentile(&values, 0.9),
+             values[values.len() - 1],
+             mean);
+}
+
+/// Prints how many games ended on each level
+fn print_levels(games: &[Stats]) {
+    let highest = games.iter().map(|g| g.level).max().unwrap_or(1);
+    let most = (1..highest + 1)
+        .map(|l| games.iter().filter(|g| g.level == l).count())
+        .max()
+        .unwrap_or(1);
+
+    println!("\nlevel reached");
+    for level in 1..highest + 1 {
+        let count = games.iter().filter(|g| g.level == level).count();
+        let bar = "#".repeat(count * 40 / most);
+        println!("{:>5} | {:<40} {}", level, bar, count);
+    }
+}
+
+fn main() {
+    let options = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\nusage: balance [--games <n>] [--seed <n>] [--max-time <seconds>] \
+                   [--bot hunter|idle] [--config <file>] [--levels <file>] \
+                   [--difficulty <easy|normal|hard|nightmare>]",
+                  e);
+        process::exit(1);
+    });
+
+    let config = match options.config {
+        Some(ref path) => Config::load(path),
+        None => Ok(Config::default()),
+    };
+    let config = config.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    let config = options.difficulty.apply(&config);
+    let script = match options.levels {
+        Some(ref path) => Script::load(path),
+        None => Ok(Script::default()),
+    };
+    let script = script.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+
+    let games: Vec<Stats> = (0..options.games)
+        .map(|i| play(options.seed.wrapping_add(u64::from(i)), &options, &config, &script))
+        .collect();
+
+    let survived = games.iter().filter(|g| g.survived).count();
+    println!("{} games with the {:?} bot on {}, seeds {} to {}",
+             games.len(),
+             options.strategy,
+             options.difficulty.get_name(),
+             options.seed,
+             options.seed.wrapping_add(u64::from(options.games - 1)));
+    println!("{} still alive after {} seconds\n", survived, options.max_time);
+
+    println!("{:<16}{:>9}{:>9}{:>9}{:>9}{:>9}{:>9}",
+             "",
+             "min",
+             "p10",
+             "median",
+             "p90",
+             "max",
+             "mean");
+    print_row("level", column(&games, |g| g.level as f64));
+    print_row("survival (s)", column(&games, |g| g.survival));
+    print_row("score", column(&games, |g| g.score as f64));
+    print_row("bullet damage", column(&games, |g| g.bullet_damage as f64));
+    print_row("orb damage", column(&games, |g| g.orb_damage as f64));
+    print_row("shots fired", column(&games, |g| g.shots as f64));
+    print_row("accuracy (%)", column(&games, |g| g.accuracy * 100.0));
+    if config.director.enabled {
+        print_row("final rating", column(&games, |g| g.rating));
+    }
+
+    print_levels(&games);
+}
@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use collision::shape::Shape;
+use constants::game_constants::MAXGRIDCELLS;
+use vector::Vector;
+
+/// Uniform grid used as a broadphase. Things are put in every cell their
+/// bounding circle overlaps, so a query only has to look at the things in
+/// the cells around it instead of everything in the game. Things outside the
+/// world are put in the cells at its edge.
+pub struct Grid {
+    /// Width and height of a cell
+    cell_size: f64,
+    /// Number of columns and rows covering the world
+    size: (i64, i64),
+    /// Items in each cell, keyed by the cell's column and row
+    cells: HashMap<(i64, i64), Vec<usize>>,
+}
+
+impl Grid {
+    /// Returns an empty grid covering a world of the given dimensions
+    pub fn new(cell_size: f64, dimensions: &[f64; 2]) -> Self {
+        let mut grid = Grid {
+            cell_size,
+            size: (1, 1),
+            cells: HashMap::new(),
+        };
+        grid.resize(dimensions);
+        grid
+    }
+
+    /// Changes the dimensions of the world the grid covers. Only takes
+    /// effect for items inserted after the next `clear`
+    pub fn resize(&mut self, dimensions: &[f64; 2]) {
+        let cells = |v: f64| ((v / self.cell_size).ceil() as i64).clamp(1, MAXGRIDCELLS);
+        self.size = (cells(dimensions[0]), cells(dimensions[1]));
+    }
+
+    /// Removes every item. Cells are kept so that filling the grid again
+    /// doesn't allocate
+    pub fn clear(&mut self) {
+        for items in self.cells.values_mut() {
+            items.clear();
+        }
+    }
+
+    /// Adds an item with a bounding circle
+    pub fn insert(&mut self, item: usize, pos: Vector, radius: f64) {
+        let (min, max) = self.cell_range(pos, radius);
+        for col in min.0..=max.0 {
+            for row in min.1..=max.1 {
+                self.cells.entry((col, row)).or_default().push(item);
+            }
+        }
+    }
+
+    /// Adds an item with the bounding circle of a shape
+    pub fn insert_shape(&mut self, item: usize, shape: &Shape) {
+        self.insert(item, shape.get_center(), shape.get_bounding_radius());
+    }
+
+    /// Puts every item that might overlap the circle in `out`, in ascending
+    /// order and without duplicates. Items that don't overlap it may be
+    /// included too
+    pub fn query(&self, pos: Vector, radius: f64, out: &mut Vec<usize>) {
+        out.clear();
+        let (min, max) = self.cell_range(pos, radius);
+        for col in min.0..=max.0 {
+            for row in min.1..=max.1 {
+                if let Some(items) = self.cells.get(&(col, row)) {
+                    out.extend_from_slice(items);
+                }
+            }
+        }
+        out.sort_unstable();
+        out.dedup();
+    }
+
+    /// Puts every item that might overlap a shape in `out`, like `query`
+    pub fn query_shape(&self, shape: &Shape, out: &mut Vec<usize>) {
+        self.query(shape.get_center(), shape.get_bounding_radius(), out);
+    }
+
+    /// Returns the first and last cell covered by a circle. Cells are
+    /// clamped to the world, so huge or non-finite circles can't overflow or
+    /// cover more cells than the grid has
+    fn cell_range(&self, pos: Vector, radius: f64) -> ((i64, i64), (i64, i64)) {
+        let cell = |v: f64, last: i64| ((v / self.cell_size).floor() as i64).clamp(0, last);
+        let (cols, rows) = (self.size.0 - 1, self.size.1 - 1);
+        ((cell(pos.x - radius, cols), cell(pos.y - radius, rows)),
+         (cell(pos.x + radius, cols), cell(pos.y + radius, rows)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64;
+
+    use super::*;
+
+    fn grid() -> Grid {
+        Grid::new(10.0, &[100.0, 50.0])
+    }
+
+    fn query(grid: &Grid, x: f64, y: f64, radius: f64) -> Vec<usize> {
+        let mut out = Vec::new();
+        grid.query(Vector::new(x, y), radius, &mut out);
+        out
+    }
+
+    #[test]
+    fn finds_items_in_nearby_cells_only() {
+        let mut grid = grid();
+        grid.insert(0, Vector::new(15.0, 15.0), 2.0);
+        grid.insert(1, Vector::new(85.0, 45.0), 2.0);
+        assert_eq!(query(&grid, 12.0, 18.0, 1.0), vec![0]);
+        assert_eq!(query(&grid, 50.0, 25.0, 100.0), vec![0, 1]);
+        assert!(query(&grid, 50.0, 25.0, 1.0).is_empty());
+    }
+
+    #[test]
+    fn things_outside_the_world_are_at_its_edge() {
+        let mut grid = grid();
+        grid.insert(0, Vector::new(-500.0, 25.0), 1.0);
+        grid.insert(1, Vector::new(1e12, 1e12), 1.0);
+        assert_eq!(query(&grid, -300.0, 25.0, 1.0), vec![0]);
+        assert_eq!(query(&grid, 99.0, 49.0, 1.0), vec![1]);
+    }
+
+    #[test]
+    fn huge_and_non_finite_circles_stay_in_the_world() {
+        let mut grid = grid();
+        grid.insert(0, Vector::new(f64::MAX, f64::MIN), f64::INFINITY);
+        grid.insert(1, Vector::new(f64::NAN, 0.0), f64::NAN);
+        grid.insert(2, Vector::new(0.0, 0.0), 1e300);
+        assert_eq!(grid.cells.len(), 50);
+        assert_eq!(query(&grid, f64::NEG_INFINITY, f64::NAN, f64::MAX), vec![0, 1, 2]);
+    }
+}
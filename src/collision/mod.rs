@@ -0,0 +1,25 @@
+//! Finds out which things in the game touch each other.
+
+pub mod grid;
+pub mod shape;
+
+use config::Config;
+use self::shape::Shape;
+
+/// Something that can collide with other things
+pub trait Collider {
+    /// Returns the shape the thing collides as
+    fn get_shape(&self, config: &Config) -> Shape;
+
+    /// Returns the shape covering everywhere the thing was during the last
+    /// step. Fast things override this so they can't pass through others
+    /// between two steps
+    fn get_swept_shape(&self, config: &Config) -> Shape {
+        self.get_shape(config)
+    }
+}
+
+/// Returns whether two things collided during the last step
+pub fn collide<A: Collider, B: Collider>(a: &A, b: &B, config: &Config) -> bool {
+    a.get_swept_shape(config).overlaps(&b.get_swept_shape(config))
+}
@@ -0,0 +1,388 @@
+use std::f64;
+
+use vector::Vector;
+
+/// Shape something collides as
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    /// Circle around a center
+    Circle { center: Vector, radius: f64 },
+    /// Box with its sides parallel to the axes
+    Aabb { min: Vector, max: Vector },
+    /// Box turned `rotation` radians around its center. `half_size` is half
+    /// its width and height before it is turned
+    Obb {
+        center: Vector,
+        half_size: Vector,
+        rotation: f64,
+    },
+    /// Everything within `radius` of the segment from `start` to `end`. The
+    /// area a circle covers when it moves from `start` to `end`
+    Capsule { start: Vector, end: Vector, radius: f64 },
+}
+
+/// Box as its center, half size and the unit vectors of its sides
+struct Frame {
+    center: Vector,
+    half_size: Vector,
+    axes: [Vector; 2],
+}
+
+impl Shape {
+    /// Returns the center of the shape
+    pub fn get_center(&self) -> Vector {
+        match *self {
+            Shape::Circle { center, .. } | Shape::Obb { center, .. } => center,
+            Shape::Aabb { min, max } => min.lerp(&max, 0.5),
+            Shape::Capsule { start, end, .. } => start.lerp(&end, 0.5),
+        }
+    }
+
+    /// Returns the radius of a circle around the center that holds the
+    /// whole shape
+    pub fn get_bounding_radius(&self) -> f64 {
+        match *self {
+            Shape::Circle { radius, .. } => radius,
+            Shape::Aabb { min, max } => min.dist(&max) / 2.0,
+            Shape::Obb { half_size, .. } => half_size.length(),
+            Shape::Capsule { start, end, radius } => start.dist(&end) / 2.0 + radius,
+        }
+    }
+
+    /// Returns the box covering a box that moved from `start` to `end` in
+    /// the direction it is facing
+    pub fn swept_box(start: Vector, end: Vector, half_size: Vector, rotation: f64) -> Shape {
+        Shape::Obb {
+            center: start.lerp(&end, 0.5),
+            half_size: Vector::new(half_size.x + start.dist(&end) / 2.0, half_size.y),
+            rotation,
+        }
+    }
+
+    /// Returns whether two shapes overlap. Shapes that only touch don't
+    pub fn overlaps(&self, other: &Shape) -> bool {
+        // circles are capsules that don't go anywhere
+        match (self.capsule(), other.capsule(), self.frame(), other.frame()) {
+            (Some(a), Some(b), _, _) => capsule_capsule(a, b),
+            (Some(a), None, _, Some(ref b)) |
+            (None, Some(a), Some(ref b), _) => capsule_box(a, b),
+            (None, None, Some(ref a), Some(ref b)) => box_box(a, b),
+            _ => unreachable!("every shape is a capsule or a box"),
+        }
+    }
+
+    /// Returns the frame of a box, or None if the shape isn't a box
+    fn frame(&self) -> Option<Frame> {
+        match *self {
+            Shape::Circle { .. } | Shape::Capsule { .. } => None,
+            Shape::Aabb { min, max } => {
+                Some(Frame {
+                    center: min.lerp(&max, 0.5),
+                    half_size: (max - min) / 2.0,
+                    axes: [Vector::new(1.0, 0.0), Vector::new(0.0, 1.0)],
+                })
+            }
+            Shape::Obb { center, half_size, rotation } => {
+                let axis = Vector::from_angle(rotation);
+                Some(Frame {
+                    center,
+                    half_size,
+                    axes: [axis, axis.rotate(f64::consts::FRAC_PI_2)],
+                })
+            }
+        }
+    }
+
+    /// Returns the segment and radius of a circle or capsule
+    fn capsule(&self) -> Option<(Vector, Vector, f64)> {
+        match *self {
+            Shape::Circle { center, radius } => Some((center, center, radius)),
+            Shape::Capsule { start, end, radius } => Some((start, end, radius)),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the point of the segment from `start` to `end` closest to `p`
+fn closest_on_segment(p: Vector, start: Vector, end: Vector) -> Vector {
+    let seg = end - start;
+    let length_squared = seg.length_squared();
+    if length_squared == 0.0 {
+        return start;
+    }
+    let t = ((p - start).dot(&seg) / length_squared).clamp(0.0, 1.0);
+    start + seg * t
+}
+
+/// Returns whether two segments cross or touch
+fn segments_cross(a: (Vector, Vector), b: (Vector, Vector)) -> bool {
+    let side = |p: Vector, q: Vector, r: Vector| (q - p).cross(&(r - p));
+    let (d1, d2) = (side(a.0, a.1, b.0), side(a.0, a.1, b.1));
+    let (d3, d4) = (side(b.0, b.1, a.0), side(b.0, b.1, a.1));
+
+    // segments on one line are left to the distance checks
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+/// Returns the shortest distance between two segments
+fn segment_dist(a: (Vector, Vector), b: (Vector, Vector)) -> f64 {
+    if segments_cross(a, b) {
+        return 0.0;
+    }
+    // segments that don't cross are closest at one of the ends
+    [closest_on_segment(a.0, b.0, b.1).dist(&a.0),
+     closest_on_segment(a.1, b.0, b.1).dist(&a.1),
+     closest_on_segment(b.0, a.0, a.1).dist(&b.0),
+     closest_on_segment(b.1, a.0, a.1).dist(&b.1)]
+        .iter()
+        .fold(f64::INFINITY, |min, &d| min.min(d))
+}
+
+/// Returns whether two capsules overlap
+fn capsule_capsule(a: (Vector, Vector, f64), b: (Vector, Vector, f64)) -> bool {
+    segments_cross((a.0, a.1), (b.0, b.1)) || segment_dist((a.0, a.1), (b.0, b.1)) < a.2 + b.2
+}
+
+/// Returns whether a capsule overlaps a box
+fn capsule_box(capsule: (Vector, Vector, f64), b: &Frame) -> bool {
+    let (start, end, radius) = capsule;
+
+    // work in the box's own axes, where it is an axis aligned box
+    let local = |p: Vector| {
+        let diff = p - b.center;
+        Vector::new(diff.dot(&b.axes[0]), diff.dot(&b.axes[1]))
+    };
+    let (start, end) = (local(start), local(end));
+    let half = b.half_size;
+
+    // a segment with an end inside the box
+    let inside = |p: Vector| p.x.abs() < half.x && p.y.abs() < half.y;
+    if inside(start) || inside(end) {
+        return true;
+    }
+
+    // otherwise the segment crosses a side or is closest to one
+    let corners = [Vector::new(-half.x, -half.y),
+                   Vector::new(half.x, -half.y),
+                   Vector::new(half.x, half.y),
+                   Vector::new(-half.x, half.y)];
+    (0..4).any(|i| {
+        let side = (corners[i], corners[(i + 1) % 4]);
+        segments_cross((start, end), side) || segment_dist((start, end), side) < radius
+    })
+}
+
+/// Returns whether two boxes overlap. Two boxes don't overlap if there is an
+/// axis, parallel to one of their sides, that they can be split along
+fn box_box(a: &Frame, b: &Frame) -> bool {
+    let diff = b.center - a.center;
+
+    // half the length of a box when it is projected on an axis
+    let extent = |f: &Frame, axis: &Vector| {
+        f.half_size.x * f.axes[0].dot(axis).abs() + f.half_size.y * f.axes[1].dot(axis).abs()
+    };
+
+    a.axes
+        .iter()
+        .chain(b.axes.iter())
+        .all(|axis| diff.dot(axis).abs() < extent(a, axis) + extent(b, axis))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_4;
+
+    use collision::Collider;
+    use config::Config;
+    use weapons::bullet::Bullet;
+    use super::*;
+
+    fn aabb(x0: f64, y0: f64, x1: f64, y1: f64) -> Shape {
+        Shape::Aabb {
+            min: Vector::new(x0, y0),
+            max: Vector::new(x1, y1),
+        }
+    }
+
+    fn circle(x: f64, y: f64, radius: f64) -> Shape {
+        Shape::Circle {
+            center: Vector::new(x, y),
+            radius,
+        }
+    }
+
+    /// Square of side 2 around the origin turned into a diamond. Its sides
+    /// are on |x| + |y| = √2
+    fn diamond() -> Shape {
+        Shape::Obb {
+            center: Vector::new(0.0, 0.0),
+            half_size: Vector::new(1.0, 1.0),
+            rotation: FRAC_PI_4,
+        }
+    }
+
+    /// Checks both ways round, since either shape can come first
+    fn overlap(a: &Shape, b: &Shape) -> bool {
+        let result = a.overlaps(b);
+        assert_eq!(result, b.overlaps(a), "{:?} and {:?} disagree", a, b);
+        result
+    }
+
+    #[test]
+    fn rotated_boxes_that_overlap() {
+        assert!(overlap(&diamond(), &aabb(0.6, 0.6, 2.0, 2.0)));
+        assert!(overlap(&diamond(), &aabb(-0.1, -0.1, 0.1, 0.1)));
+        let other = Shape::Obb {
+            center: Vector::new(2.0, 0.0),
+            half_size: Vector::new(1.0, 0.2),
+            rotation: 0.3,
+        };
+        assert!(overlap(&diamond(), &other));
+    }
+
+    #[test]
+    fn rotated_boxes_split_along_a_turned_side() {
+        // the bounding boxes overlap, only the diamond's own sides split them
+        assert!(!overlap(&diamond(), &aabb(0.8, 0.8, 2.0, 2.0)));
+        let other = Shape::Obb {
+            center: Vector::new(1.5, 1.5),
+            half_size: Vector::new(1.0, 0.2),
+            rotation: -FRAC_PI_4,
+        };
+        assert!(!overlap(&diamond(), &other));
+        let further = Shape::Obb {
+            center: Vector::new(3.0, 0.0),
+            half_size: Vector::new(1.0, 0.2),
+            rotation: 0.3,
+        };
+        assert!(!overlap(&diamond(), &further));
+    }
+
+    #[test]
+    fn boxes_that_touch_dont_overlap() {
+        let a = aabb(0.0, 0.0, 2.0, 2.0);
+        assert!(!overlap(&a, &aabb(2.0, 0.0, 4.0, 2.0)));
+        assert!(!overlap(&a, &aabb(2.0, 2.0, 3.0, 3.0)));
+        let turned = Shape::Obb {
+            center: Vector::new(3.0, 1.0),
+            half_size: Vector::new(1.0, 0.5),
+            rotation: 0.0,
+        };
+        assert!(!overlap(&a, &turned));
+        assert!(overlap(&a, &aabb(1.999, 0.0, 4.0, 2.0)));
+    }
+
+    #[test]
+    fn circles_and_rotated_boxes() {
+        // inside, over a side and over a corner
+        assert!(overlap(&diamond(), &circle(0.1, 0.0, 0.05)));
+        assert!(overlap(&diamond(), &circle(1.0, 1.0, 0.5)));
+        assert!(overlap(&diamond(), &circle(1.8, 0.0, 0.5)));
+        // in the bounding box of the diamond but off its side
+        assert!(!overlap(&diamond(), &circle(1.2, 1.2, 0.3)));
+        assert!(!overlap(&diamond(), &circle(1.8, 0.0, 0.3)));
+    }
+
+    #[test]
+    fn circles_that_touch_dont_overlap() {
+        assert!(!overlap(&aabb(-2.0, -1.0, 2.0, 1.0), &circle(3.0, 0.0, 1.0)));
+        assert!(overlap(&aabb(-2.0, -1.0, 2.0, 1.0), &circle(2.9, 0.0, 1.0)));
+        assert!(!overlap(&circle(0.0, 0.0, 1.0), &circle(3.0, 0.0, 2.0)));
+        assert!(overlap(&circle(0.0, 0.0, 1.0), &circle(2.9, 0.0, 2.0)));
+    }
+
+    fn v(x: f64, y: f64) -> Vector {
+        Vector::new(x, y)
+    }
+
+    fn capsule(start: Vector, end: Vector, radius: f64) -> Shape {
+        Shape::Capsule { start, end, radius }
+    }
+
+    #[test]
+    fn segments_that_cross() {
+        assert!(segments_cross((v(0.0, 0.0), v(2.0, 2.0)), (v(0.0, 2.0), v(2.0, 0.0))));
+        assert_eq!(segment_dist((v(0.0, 0.0), v(2.0, 2.0)), (v(0.0, 2.0), v(2.0, 0.0))), 0.0);
+        assert!(!segments_cross((v(0.0, 0.0), v(1.0, 1.0)), (v(0.0, 3.0), v(3.0, 0.0))));
+        assert_eq!(segment_dist((v(0.0, 0.0), v(1.0, 0.0)), (v(3.0, -1.0), v(3.0, 1.0))), 2.0);
+    }
+
+    #[test]
+    fn degenerate_segments_are_left_to_the_distance() {
+        // on one line, overlapping or not
+        let a = (v(0.0, 0.0), v(2.0, 0.0));
+        assert!(!segments_cross(a, (v(1.0, 0.0), v(3.0, 0.0))));
+        assert_eq!(segment_dist(a, (v(1.0, 0.0), v(3.0, 0.0))), 0.0);
+        assert!(!segments_cross(a, (v(3.0, 0.0), v(5.0, 0.0))));
+        assert_eq!(segment_dist(a, (v(3.0, 0.0), v(5.0, 0.0))), 1.0);
+        // an end on the other segment
+        assert!(!segments_cross(a, (v(1.0, 0.0), v(1.0, 1.0))));
+        assert_eq!(segment_dist(a, (v(1.0, 0.0), v(1.0, 1.0))), 0.0);
+        // a segment that is a point
+        let p = (v(1.0, 1.0), v(1.0, 1.0));
+        assert!(!segments_cross(a, p));
+        assert!(!segments_cross(p, p));
+        assert_eq!(segment_dist(a, p), 1.0);
+        assert_eq!(segment_dist(p, (v(4.0, 5.0), v(4.0, 5.0))), 5.0);
+    }
+
+    #[test]
+    fn capsules() {
+        let a = capsule(v(0.0, 0.0), v(4.0, 0.0), 1.0);
+        assert!(overlap(&a, &capsule(v(2.0, -3.0), v(2.0, 3.0), 0.1)));
+        assert!(overlap(&a, &capsule(v(5.0, 0.0), v(8.0, 0.0), 0.5)));
+        assert!(!overlap(&a, &capsule(v(6.0, 0.0), v(8.0, 0.0), 0.5)));
+        assert!(!overlap(&a, &capsule(v(0.0, 3.0), v(4.0, 3.0), 2.0)));
+        assert!(overlap(&a, &circle(2.0, 1.5, 0.6)));
+        assert!(!overlap(&a, &circle(6.0, 0.0, 1.0)));
+        // a capsule that doesn't go anywhere is a circle
+        assert!(overlap(&capsule(v(1.0, 1.0), v(1.0, 1.0), 1.0), &circle(2.5, 1.0, 0.6)));
+    }
+
+    #[test]
+    fn capsules_and_boxes() {
+        let b = aabb(-1.0, -1.0, 1.0, 1.0);
+        // straight through with both ends outside
+        assert!(overlap(&b, &capsule(v(-5.0, 0.0), v(5.0, 0.0), 0.0)));
+        assert!(overlap(&diamond(), &capsule(v(-5.0, -5.0), v(5.0, 5.0), 0.0)));
+        // past a side or a corner
+        assert!(overlap(&b, &capsule(v(-5.0, 1.5), v(5.0, 1.5), 0.6)));
+        assert!(!overlap(&b, &capsule(v(-5.0, 1.5), v(5.0, 1.5), 0.4)));
+        assert!(overlap(&b, &capsule(v(3.0, 0.0), v(1.5, 1.5), 0.8)));
+        // along a side
+        assert!(overlap(&b, &capsule(v(-3.0, 1.0), v(3.0, 1.0), 0.1)));
+        assert!(!overlap(&diamond(), &capsule(v(0.0, 2.0), v(2.0, 0.0), 0.3)));
+    }
+
+    #[test]
+    fn swept_box_covers_the_way_between() {
+        let swept = Shape::swept_box(v(0.0, 0.0), v(10.0, 0.0), v(1.0, 1.0), 0.0);
+        assert_eq!(swept,
+                   Shape::Obb {
+                       center: v(5.0, 0.0),
+                       half_size: v(6.0, 1.0),
+                       rotation: 0.0,
+                   });
+        assert!(overlap(&swept, &circle(5.0, 0.0, 0.5)));
+        assert!(!overlap(&swept, &circle(5.0, 2.0, 0.5)));
+    }
+
+    #[test]
+    fn fast_bullets_dont_pass_through() {
+        let mut config = Config::default();
+        // far enough in a step to jump over a whole enemy
+        config.bullet.velocity = 12000.0;
+        let dimensions = [800.0, 600.0];
+
+        for &(rotation, enemy) in &[(0.0, v(200.0, 300.0)), (FRAC_PI_4, v(170.7, 370.7))] {
+            let mut bullet = Bullet::new(100.0, 300.0, rotation, true);
+            bullet.update(1.0 / 60.0, &dimensions, &config);
+            let enemy = circle(enemy.x, enemy.y, 10.0);
+
+            // the bullet was on either side of the enemy
+            assert!(!bullet.get_shape(&config).overlaps(&enemy));
+            assert!(!Bullet::new(100.0, 300.0, rotation, true).get_shape(&config).overlaps(&enemy));
+            assert!(bullet.get_swept_shape(&config).overlaps(&enemy));
+        }
+    }
+}
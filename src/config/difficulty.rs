@@ -0,0 +1,132 @@
+//! Presets that make the game easier or harder by scaling a config.
+
+use super::Config;
+
+/// How hard the game is. Scales the values of a config, so it works on top
+/// of whatever the config file sets
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Weaker enemies that shoot less and miss more, and a tougher player
+    Easy,
+    /// The config as it is
+    #[default]
+    Normal,
+    /// Tougher enemies that shoot more often and more accurately
+    Hard,
+    /// Even tougher enemies and a player that hardly heals
+    Nightmare,
+}
+
+/// Factors a difficulty scales the config by
+struct Scale {
+    enemy_health: f64,
+    enemy_cooldown: f64,
+    shooting_error: f64,
+    volley_size: f64,
+    orb_cooldown: f64,
+    player_health: f64,
+    kill_healing: f64,
+}
+
+impl Difficulty {
+    /// Every difficulty, from the easiest to the hardest
+    pub const ALL: [Difficulty; 4] = [Difficulty::Easy,
+                                      Difficulty::Normal,
+                                      Difficulty::Hard,
+                                      Difficulty::Nightmare];
+
+    /// Returns the name of the difficulty, as it is given on the command
+    /// line and written in the high scores
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+            Difficulty::Nightmare => "nightmare",
+        }
+    }
+
+    /// Returns the difficulty with a name. Case is ignored
+    pub fn parse(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        Difficulty::ALL.iter().find(|d| d.get_name() == name).cloned()
+    }
+
+    /// Returns the next harder difficulty, or the easiest after the hardest
+    pub fn next(&self) -> Self {
+        let i = Difficulty::ALL.iter().position(|d| d == self).unwrap_or(0);
+        Difficulty::ALL[(i + 1) % Difficulty::ALL.len()]
+    }
+
+    /// Returns the next easier difficulty, or the hardest after the easiest
+    pub fn previous(&self) -> Self {
+        let i = Difficulty::ALL.iter().position(|d| d == self).unwrap_or(0);
+        Difficulty::ALL[(i + Difficulty::ALL.len() - 1) % Difficulty::ALL.len()]
+    }
+
+    /// Returns the factors of the difficulty
+    fn get_scale(&self) -> Scale {
+        match *self {
+            Difficulty::Easy => Scale {
+                enemy_health: 0.6,
+                enemy_cooldown: 1.5,
+                shooting_error: 2.0,
+                volley_size: 0.6,
+                orb_cooldown: 1.5,
+                player_health: 1.5,
+                kill_healing: 2.0,
+            },
+            Difficulty::Normal => Scale {
+                enemy_health: 1.0,
+                enemy_cooldown: 1.0,
+                shooting_error: 1.0,
+                volley_size: 1.0,
+                orb_cooldown: 1.0,
+                player_health: 1.0,
+                kill_healing: 1.0,
+            },
+            Difficulty::Hard => Scale {
+                enemy_health: 1.4,
+                enemy_cooldown: 0.8,
+                shooting_error: 0.6,
+                volley_size: 1.4,
+                orb_cooldown: 0.75,
+                player_health: 0.8,
+                kill_healing: 1.0,
+            },
+            Difficulty::Nightmare => Scale {
+                enemy_health: 2.0,
+                enemy_cooldown: 0.6,
+                shooting_error: 0.3,
+                volley_size: 2.0,
+                orb_cooldown: 0.5,
+                player_health: 0.5,
+                kill_healing: 0.5,
+            },
+        }
+    }
+
+    /// Returns `config` scaled to the difficulty. Healths and volleys that
+    /// weren't 0 stay at least 1
+    pub fn apply(&self, config: &Config) -> Config {
+        let scale = self.get_scale();
+        let mut config = config.clone();
+
+        config.enemy.start_health = multiply(config.enemy.start_health, scale.enemy_health);
+        config.enemy.cooldown *= scale.enemy_cooldown;
+        config.enemy.shooting_error *= scale.shooting_error;
+        config.boss.volley_size = multiply(config.boss.volley_size, scale.volley_size);
+        config.orb.cooldown *= scale.orb_cooldown;
+        config.player.start_health = multiply(config.player.start_health, scale.player_health);
+        config.game.enemy_kill = (f64::from(config.game.enemy_kill) * scale.kill_healing) as u32;
+        config.game.boss_kill = (f64::from(config.game.boss_kill) * scale.kill_healing) as u32;
+        config
+    }
+}
+
+/// Returns a whole number times a factor, rounded down but not below 1
+/// unless it was 0
+fn multiply(value: u32, factor: f64) -> u32 {
+    let scaled = (f64::from(value) * factor) as u32;
+    if value == 0 { 0 } else { scaled.max(1) }
+}
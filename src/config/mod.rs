@@ -0,0 +1,450 @@
+//! Gameplay values that can be tuned without recompiling. Every value
+//! defaults to the matching constant in `constants`, so a config file only
+//! needs the keys it changes. A running game can pick up changes to the file
+//! through a `watch::Watcher`.
+
+pub mod difficulty;
+pub mod toml;
+pub mod watch;
+
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use self::toml::Value;
+use constants::{orb_constants, bullet_constants, game_constants};
+
+/// Tuning values for the player
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerConfig {
+    /// Diameter of the player
+    pub diameter: f64,
+    /// How much to move back when player hits the end of screen
+    pub moveback: f64,
+    /// Velocity of player
+    pub velocity: f64,
+    /// Bullet must be this amount inside to count as a hit
+    pub epsilon: f64,
+    /// Distance desired and actual position can be
+    pub stop_distance: f64,
+    /// Cooldown between bursts
+    pub burst_cooldown: f64,
+    /// Cooldown between shots
+    pub shot_cooldown: f64,
+    /// Shots in a burst
+    pub start_shots: u32,
+    /// Starting health for player
+    pub start_health: u32,
+    /// Speed of a dash in multiples of the velocity
+    pub dash_speed: f64,
+    /// Length of a dash
+    pub dash_time: f64,
+    /// Cooldown between dashes
+    pub dash_cooldown: f64,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        use constants::player_constants::*;
+        PlayerConfig {
+            diameter: PLAYERD,
+            moveback: MOVEBACK,
+            velocity: VEL,
+            epsilon: EPSILON,
+            stop_distance: DDISTANCE,
+            burst_cooldown: BURSTCOOLDOWN,
+            shot_cooldown: SHOTCOOLDOWN,
+            start_shots: STARTSHOTS,
+            start_health: STARTHEALTH,
+            dash_speed: DASHSPEED,
+            dash_time: DASHTIME,
+            dash_cooldown: DASHCOOLDOWN,
+        }
+    }
+}
+
+/// Tuning values for enemies
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnemyConfig {
+    /// How much to move back when hitting the wall
+    pub moveback: f64,
+    /// Diameter of the enemy
+    pub diameter: f64,
+    /// Velocity of the enemy
+    pub velocity: f64,
+    /// Starting health of enemy
+    pub start_health: u32,
+    /// Bullet collision error
+    pub epsilon: f64,
+    /// Cooldown for shooting
+    pub cooldown: f64,
+    /// Largest error in radians when shooting
+    pub shooting_error: f64,
+    /// Diameter of the orb
+    pub orb_diameter: f64,
+    /// Diameter of the orb orbit
+    pub orbit_diameter: f64,
+}
+
+impl Default for EnemyConfig {
+    fn default() -> Self {
+        use constants::enemy_constants::*;
+        EnemyConfig {
+            moveback: MOVEBACK,
+            diameter: ENEMYD,
+            velocity: VEL,
+            start_health: STARTHEALTH,
+            epsilon: EPSILON,
+            cooldown: COOLDOWN,
+            shooting_error: SHOOTINGERR,
+            orb_diameter: ORBD,
+            orbit_diameter: ORBITD,
+        }
+    }
+}
+
+/// Tuning values for bosses
+#[derive(Clone, Debug, PartialEq)]
+pub struct BossConfig {
+    /// Cooldown for shooting
+    pub cooldown: f64,
+    /// Start health of the boss
+    pub start_health: u32,
+    /// Boss Diameter
+    pub diameter: f64,
+    /// Bullet collision error
+    pub epsilon: f64,
+    /// Rotation speed in radians per second
+    pub rotation_speed: f64,
+    /// Bullets shot in a volley
+    pub volley_size: u32,
+}
+
+impl Default for BossConfig {
+    fn default() -> Self {
+        use constants::boss_constants::*;
+        BossConfig {
+            cooldown: COOLDOWN,
+            start_health: STARTHEALTH,
+            diameter: BOSSD,
+            epsilon: EPSILON,
+            rotation_speed: ROTMULT,
+            volley_size: TOTBULL,
+        }
+    }
+}
+
+/// Tuning values for orbs
+#[derive(Clone, Debug, PartialEq)]
+pub struct OrbConfig {
+    /// Rotation speed in multiples of pi per second
+    pub velocity: f64,
+    /// Time an orb stays inactive after hitting the player
+    pub cooldown: f64,
+}
+
+impl Default for OrbConfig {
+    fn default() -> Self {
+        OrbConfig {
+            velocity: orb_constants::VEL,
+            cooldown: orb_constants::COOLDOWN,
+        }
+    }
+}
+
+/// Tuning values for bullets
+#[derive(Clone, Debug, PartialEq)]
+pub struct BulletConfig {
+    /// Speed of bullets in pixels per second
+    pub velocity: f64,
+    /// Side length of bullets
+    pub side_length: f64,
+}
+
+impl Default for BulletConfig {
+    fn default() -> Self {
+        BulletConfig {
+            velocity: bullet_constants::VEL,
+            side_length: bullet_constants::SIDELENGTH,
+        }
+    }
+}
+
+/// Tuning values for the game as a whole
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameConfig {
+    /// Health added for boss kill
+    pub boss_kill: u32,
+    /// Health added for enemy kill
+    pub enemy_kill: u32,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            boss_kill: game_constants::BOSSKILL,
+            enemy_kill: game_constants::ENEMYKILL,
+        }
+    }
+}
+
+/// Tuning values for the director, which makes the game harder or easier
+/// depending on how the player is doing
+#[derive(Clone, Debug, PartialEq)]
+pub struct DirectorConfig {
+    /// Whether the director adjusts the game
+    pub enabled: bool,
+    /// Seconds between adjustments
+    pub interval: f64,
+    /// Seconds of recent play looked at
+    pub window: f64,
+    /// How far the rating moves in one adjustment
+    pub step: f64,
+    /// Health lost per second above which the game is made easier
+    pub damage_rate: f64,
+    /// Kills per second above which the game is made harder
+    pub kill_rate: f64,
+    /// Fraction of the starting health below which the game is made easier
+    pub low_health: f64,
+    /// Reinforcements per kill added or taken away at the rating bounds
+    pub spawn_range: u32,
+    /// Fraction cooldowns of enemies and bosses shrink or grow by at the
+    /// rating bounds
+    pub fire_range: f64,
+    /// Bosses added to or taken from boss spawns at the rating bounds
+    pub boss_range: u32,
+}
+
+impl Default for DirectorConfig {
+    fn default() -> Self {
+        use constants::director_constants::*;
+        DirectorConfig {
+            enabled: ENABLED,
+            interval: INTERVAL,
+            window: WINDOW,
+            step: STEP,
+            damage_rate: DAMAGERATE,
+            kill_rate: KILLRATE,
+            low_health: LOWHEALTH,
+            spawn_range: SPAWNRANGE,
+            fire_range: FIRERANGE,
+            boss_range: BOSSRANGE,
+        }
+    }
+}
+
+/// Every tunable gameplay value
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Config {
+    pub player: PlayerConfig,
+    pub enemy: EnemyConfig,
+    pub boss: BossConfig,
+    pub orb: OrbConfig,
+    pub bullet: BulletConfig,
+    pub game: GameConfig,
+    pub director: DirectorConfig,
+}
+
+/// Error returned when a config file can't be used
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// The file isn't valid TOML
+    Syntax(toml::SyntaxError),
+    /// A table that the config doesn't have
+    UnknownTable { line: usize, table: String },
+    /// A key that the config doesn't have. Holds the full name, like
+    /// `player.velocity`
+    UnknownKey { line: usize, key: String },
+    /// A key whose value can't be used
+    InvalidValue { line: usize, key: String, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ConfigError::Io(ref e) => write!(f, "could not read config: {}", e),
+            ConfigError::Syntax(ref e) => write!(f, "config {}", e),
+            ConfigError::UnknownTable { line, ref table } => {
+                write!(f, "config line {}: unknown table [{}]", line, table)
+            }
+            ConfigError::UnknownKey { line, ref key } => {
+                write!(f, "config line {}: unknown key {}", line, key)
+            }
+            ConfigError::InvalidValue { line, ref key, ref message } => {
+                write!(f, "config line {}: {}: {}", line, key, message)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+/// Returns a positive number
+pub(crate) fn positive(value: &Value) -> Result<f64, String> {
+    match value.as_f64() {
+        Some(f) if f > 0.0 => Ok(f),
+        Some(_) => Err("must be greater than 0".to_string()),
+        None => Err("expected a number".to_string()),
+    }
+}
+
+/// Returns a number that is 0 or more
+pub(crate) fn non_negative(value: &Value) -> Result<f64, String> {
+    match value.as_f64() {
+        Some(f) if f >= 0.0 => Ok(f),
+        Some(_) => Err("must not be negative".to_string()),
+        None => Err("expected a number".to_string()),
+    }
+}
+
+/// Returns a number from 0 up to but not including 1
+pub(crate) fn fraction(value: &Value) -> Result<f64, String> {
+    match value.as_f64() {
+        Some(f) if (0.0..1.0).contains(&f) => Ok(f),
+        Some(_) => Err("must be at least 0 and less than 1".to_string()),
+        None => Err("expected a number".to_string()),
+    }
+}
+
+/// Returns a boolean
+pub(crate) fn boolean(value: &Value) -> Result<bool, String> {
+    value.as_bool().ok_or_else(|| "expected true or false".to_string())
+}
+
+/// Returns a whole number that is 0 or more
+pub(crate) fn count(value: &Value) -> Result<u32, String> {
+    match value.as_i64() {
+        Some(i) if i >= 0 && i <= i64::from(u32::MAX) => Ok(i as u32),
+        Some(_) => Err("must be a whole number from 0 to 4294967295".to_string()),
+        None => Err("expected a whole number".to_string()),
+    }
+}
+
+/// Returns a whole number that is 1 or more
+pub(crate) fn at_least_one(value: &Value) -> Result<u32, String> {
+    match count(value)? {
+        0 => Err("must be at least 1".to_string()),
+        n => Ok(n),
+    }
+}
+
+impl Config {
+    /// Reads a config file. Keys that are left out keep their default value
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Config::parse(&text)
+    }
+
+    /// Reads a config from text. Keys that are left out keep their default
+    /// value
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut config = Config::default();
+
+        for table in toml::parse(text).map_err(ConfigError::Syntax)? {
+            let known = ["", "player", "enemy", "boss", "orb", "bullet", "game", "director"];
+            if table.array || !known.contains(&table.name.as_str()) {
+                return Err(ConfigError::UnknownTable {
+                    line: table.line,
+                    table: table.name,
+                });
+            }
+
+            for entry in &table.entries {
+                let key = if table.name.is_empty() {
+                    entry.key.clone()
+                } else {
+                    format!("{}.{}", table.name, entry.key)
+                };
+
+                match config.set(&table.name, &entry.key, &entry.value) {
+                    Some(Ok(())) => {}
+                    Some(Err(message)) => {
+                        return Err(ConfigError::InvalidValue {
+                            line: entry.line,
+                            key,
+                            message,
+                        })
+                    }
+                    None => return Err(ConfigError::UnknownKey { line: entry.line, key }),
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Sets a single value. Returns None if there is no such key
+    fn set(&mut self, table: &str, key: &str, value: &Value) -> Option<Result<(), String>> {
+        // assigns the value if it passes the check
+        macro_rules! set {
+            ($field:expr, $check:ident) => {
+                $check(value).map(|v| $field = v)
+            }
+        }
+
+        let result = match (table, key) {
+            ("player", "diameter") => set!(self.player.diameter, positive),
+            ("player", "moveback") => set!(self.player.moveback, non_negative),
+            ("player", "velocity") => set!(self.player.velocity, non_negative),
+            ("player", "epsilon") => set!(self.player.epsilon, non_negative),
+            ("player", "stop_distance") => set!(self.player.stop_distance, non_negative),
+            ("player", "burst_cooldown") => set!(self.player.burst_cooldown, non_negative),
+            ("player", "shot_cooldown") => set!(self.player.shot_cooldown, non_negative),
+            ("player", "start_shots") => set!(self.player.start_shots, at_least_one),
+            ("player", "start_health") => set!(self.player.start_health, at_least_one),
+            ("player", "dash_speed") => set!(self.player.dash_speed, non_negative),
+            ("player", "dash_time") => set!(self.player.dash_time, non_negative),
+            ("player", "dash_cooldown") => set!(self.player.dash_cooldown, non_negative),
+
+            ("enemy", "moveback") => set!(self.enemy.moveback, non_negative),
+            ("enemy", "diameter") => set!(self.enemy.diameter, positive),
+            ("enemy", "velocity") => set!(self.enemy.velocity, non_negative),
+            ("enemy", "start_health") => set!(self.enemy.start_health, at_least_one),
+            ("enemy", "epsilon") => set!(self.enemy.epsilon, non_negative),
+            ("enemy", "cooldown") => set!(self.enemy.cooldown, positive),
+            ("enemy", "shooting_error") => set!(self.enemy.shooting_error, non_negative),
+            ("enemy", "orb_diameter") => set!(self.enemy.orb_diameter, non_negative),
+            ("enemy", "orbit_diameter") => set!(self.enemy.orbit_diameter, non_negative),
+
+            ("boss", "cooldown") => set!(self.boss.cooldown, positive),
+            ("boss", "start_health") => set!(self.boss.start_health, at_least_one),
+            ("boss", "diameter") => set!(self.boss.diameter, positive),
+            ("boss", "epsilon") => set!(self.boss.epsilon, non_negative),
+            ("boss", "rotation_speed") => set!(self.boss.rotation_speed, non_negative),
+            ("boss", "volley_size") => set!(self.boss.volley_size, count),
+
+            ("orb", "velocity") => set!(self.orb.velocity, non_negative),
+            ("orb", "cooldown") => set!(self.orb.cooldown, non_negative),
+
+            ("bullet", "velocity") => set!(self.bullet.velocity, positive),
+            ("bullet", "side_length") => set!(self.bullet.side_length, positive),
+
+            ("game", "boss_kill") => set!(self.game.boss_kill, count),
+            ("game", "enemy_kill") => set!(self.game.enemy_kill, count),
+
+            ("director", "enabled") => set!(self.director.enabled, boolean),
+            ("director", "interval") => set!(self.director.interval, positive),
+            ("director", "window") => set!(self.director.window, positive),
+            ("director", "step") => set!(self.director.step, non_negative),
+            ("director", "damage_rate") => set!(self.director.damage_rate, non_negative),
+            ("director", "kill_rate") => set!(self.director.kill_rate, non_negative),
+            ("director", "low_health") => set!(self.director.low_health, non_negative),
+            ("director", "spawn_range") => set!(self.director.spawn_range, count),
+            ("director", "fire_range") => set!(self.director.fire_range, fraction),
+            ("director", "boss_range") => set!(self.director.boss_range, count),
+
+            _ => return None,
+        };
+
+        Some(result)
+    }
+}
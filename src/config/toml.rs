@@ -0,0 +1,452 @@
+//! Reads the subset of TOML used by the game's data files: `[table]` and
+//! `[[array]]` headers, which may have dotted names, `key = value` pairs,
+//! comments, and values that are integers, floats, booleans, strings or
+//! single line arrays.
+//!
+//! The files only hold flat tables of numbers, names and short lists, and
+//! every error has to name the line of the key it is about. Keeping every
+//! entry with its line is simple here, while the `toml` crate would have to
+//! be paired with span tracking to do it, so the game reads the subset
+//! itself.
+//!
+//! Not supported, and refused with an error rather than misread:
+//!
+//! * Quoted and dotted keys, such as `"a b" = 1` or `a.b = 1`
+//! * Inline tables, such as `a = { b = 1 }`
+//! * Literal strings in single quotes and multi-line strings
+//! * Arrays over more than one line
+//! * Hexadecimal, octal and binary integers, `inf` and `nan`
+//! * Dates and times
+//! * Escapes other than `\n`, `\t`, `\"` and `\\`
+//!
+//! It is also looser than TOML in places: numbers are whatever Rust parses
+//! once underscores are removed, so `.5` and `1__0` are read, and an array
+//! may mix types.
+
+use std::fmt;
+
+/// A value on the right hand side of `key = value`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    String(String),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    /// Returns the value as a float. Integers are converted
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Value::Integer(i) => Some(i as f64),
+            Value::Float(f) => Some(f),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is an integer
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Value::Integer(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a boolean
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Boolean(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is an array
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match *self {
+            Value::Array(ref a) => Some(a),
+            _ => None,
+        }
+    }
+}
+
+/// A `key = value` line
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub key: String,
+    pub value: Value,
+    /// Line the entry is on, starting at 1
+    pub line: usize,
+}
+
+/// A table and the entries under its header. Entries before the first
+/// header are put in a table with an empty name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    pub name: String,
+    /// Whether the header was `[[name]]`
+    pub array: bool,
+    /// Line of the header, starting at 1
+    pub line: usize,
+    pub entries: Vec<Entry>,
+}
+
+/// Error returned for text that isn't valid
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Parses a document into its tables, in the order they appear
+pub fn parse(text: &str) -> Result<Vec<Table>, SyntaxError> {
+    let mut tables = vec![Table {
+                              name: String::new(),
+                              array: false,
+                              line: 0,
+                              entries: Vec::new(),
+                          }];
+
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let error = |message: &str| {
+            SyntaxError {
+                line,
+                message: message.to_string(),
+            }
+        };
+        let text = strip_comment(raw).trim();
+
+        if text.is_empty() {
+            continue;
+        }
+
+        // table headers
+        if text.starts_with('[') {
+            let array = text.starts_with("[[");
+            let (open, close) = if array { ("[[", "]]") } else { ("[", "]") };
+            if !text.ends_with(close) || text.len() < open.len() + close.len() {
+                return Err(error("unclosed table header"));
+            }
+
+            let name = text[open.len()..text.len() - close.len()].trim();
+            if !name.split('.').all(|part| !part.is_empty() && part.chars().all(is_key_char)) {
+                return Err(error(&format!("invalid table name: {}", name)));
+            }
+            if tables.iter().any(|t| t.name == name && t.array != array) {
+                return Err(error(&format!("table {} is both a table and an array", name)));
+            }
+            if !array && tables.iter().any(|t| t.name == name) {
+                return Err(error(&format!("table {} is defined twice", name)));
+            }
+
+            tables.push(Table {
+                name: name.to_string(),
+                array,
+                line,
+                entries: Vec::new(),
+            });
+            continue;
+        }
+
+        // key value pairs
+        let eq = text.find('=').ok_or_else(|| error("expected key = value"))?;
+        let key = text[..eq].trim();
+        if key.is_empty() || !key.chars().all(is_key_char) {
+            return Err(error(&format!("invalid key: {}", key)));
+        }
+
+        let value = parse_value(text[eq + 1..].trim()).map_err(|m| error(&m))?;
+
+        let table = tables.last_mut().unwrap();
+        if table.entries.iter().any(|e| e.key == key) {
+            return Err(error(&format!("key {} is defined twice", key)));
+        }
+        table.entries.push(Entry {
+            key: key.to_string(),
+            value,
+            line,
+        });
+    }
+
+    Ok(tables)
+}
+
+/// Returns whether a character can be part of a bare key
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Removes a comment from the end of a line. `#` inside strings is kept
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Parses the right hand side of `key = value`
+fn parse_value(text: &str) -> Result<Value, String> {
+    if text.is_empty() {
+        return Err("missing value".to_string());
+    }
+
+    if text.starts_with('"') {
+        return parse_string(text);
+    }
+
+    if text.starts_with('[') {
+        if !text.ends_with(']') {
+            return Err("unclosed array".to_string());
+        }
+        let mut values = Vec::new();
+        for item in split_array(&text[1..text.len() - 1])? {
+            values.push(parse_value(item.trim())?);
+        }
+        return Ok(Value::Array(values));
+    }
+
+    match text {
+        "true" => return Ok(Value::Boolean(true)),
+        "false" => return Ok(Value::Boolean(false)),
+        _ => {}
+    }
+
+    let number = text.replace('_', "");
+    if let Ok(i) = number.parse::<i64>() {
+        return Ok(Value::Integer(i));
+    }
+    match number.parse::<f64>() {
+        Ok(f) if f.is_finite() => Ok(Value::Float(f)),
+        _ => Err(format!("invalid value: {}", text)),
+    }
+}
+
+/// Parses a quoted string
+fn parse_string(text: &str) -> Result<Value, String> {
+    let mut out = String::new();
+    let mut chars = text[1..].chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                return if chars.as_str().trim().is_empty() {
+                    Ok(Value::String(out))
+                } else {
+                    Err("unexpected text after string".to_string())
+                };
+            }
+            '\\' => {
+                match chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some('"') => out.push('"'),
+                    Some('\\') => out.push('\\'),
+                    _ => return Err("invalid escape in string".to_string()),
+                }
+            }
+            c => out.push(c),
+        }
+    }
+
+    Err("unclosed string".to_string())
+}
+
+/// Splits the inside of an array on the commas that aren't nested in another
+/// array or a string
+fn split_array(text: &str) -> Result<Vec<&str>, String> {
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '[' if !in_string => depth += 1,
+            ']' if !in_string => depth -= 1,
+            ',' if !in_string && depth == 0 => {
+                items.push(&text[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    if in_string || depth != 0 {
+        return Err("unbalanced array".to_string());
+    }
+
+    // allow a trailing comma and empty arrays
+    let last = &text[start..];
+    if !last.trim().is_empty() {
+        items.push(last);
+    }
+    if items.iter().any(|i| i.trim().is_empty()) {
+        return Err("empty array element".to_string());
+    }
+
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the value of the only entry of a one line document
+    fn value(line: &str) -> Result<Value, SyntaxError> {
+        let mut tables = parse(line)?;
+        Ok(tables.remove(0).entries.remove(0).value)
+    }
+
+    /// Returns the message of the error a document gives
+    fn error(text: &str) -> String {
+        parse(text).expect_err("text should not parse").message
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(value("a = 12").unwrap(), Value::Integer(12));
+        assert_eq!(value("a = -1_000").unwrap(), Value::Integer(-1000));
+        assert_eq!(value("a = 2.5").unwrap(), Value::Float(2.5));
+        assert_eq!(value("a = 1e3").unwrap(), Value::Float(1000.0));
+        assert_eq!(value("a = true").unwrap(), Value::Boolean(true));
+        assert_eq!(value("a = \"x\"").unwrap(), Value::String("x".to_string()));
+        assert_eq!(value("a = 1 # one").unwrap(), Value::Integer(1));
+        // looser than TOML
+        assert_eq!(value("a = .5").unwrap(), Value::Float(0.5));
+        assert_eq!(value("a = 1__0").unwrap(), Value::Integer(10));
+    }
+
+    #[test]
+    fn non_finite_numbers_are_refused() {
+        for text in &["inf", "-inf", "nan", "NaN", "1e999", "infinity"] {
+            let line = format!("a = {}", text);
+            assert_eq!(value(&line).unwrap_err().message, format!("invalid value: {}", text));
+        }
+    }
+
+    #[test]
+    fn comments_inside_strings_are_kept() {
+        assert_eq!(value("a = \"# not a comment\" # a comment").unwrap(),
+                   Value::String("# not a comment".to_string()));
+        assert_eq!(value("a = [\"#\", \"b\"] # c").unwrap(),
+                   Value::Array(vec![Value::String("#".to_string()),
+                                     Value::String("b".to_string())]));
+        // an escaped quote doesn't end the string
+        assert_eq!(value("a = \"x\\\" # y\"").unwrap(),
+                   Value::String("x\" # y".to_string()));
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(value("a = \"1\\n2\\t3\\\\4\\\"5\"").unwrap(),
+                   Value::String("1\n2\t3\\4\"5".to_string()));
+        assert_eq!(error("a = \"\\q\""), "invalid escape in string");
+        assert_eq!(error("a = \"abc"), "unclosed string");
+        assert_eq!(error("a = \"abc\\\""), "unclosed string");
+        assert_eq!(error("a = \"abc\" d"), "unexpected text after string");
+    }
+
+    #[test]
+    fn nested_arrays() {
+        assert_eq!(value("a = [[1, 2], [], [\"]\", [3.5]],]").unwrap(),
+                   Value::Array(vec![Value::Array(vec![Value::Integer(1), Value::Integer(2)]),
+                                     Value::Array(vec![]),
+                                     Value::Array(vec![Value::String("]".to_string()),
+                                                       Value::Array(vec![Value::Float(3.5)])])]));
+        assert_eq!(error("a = [1, [2"), "unclosed array");
+        assert_eq!(error("a = [1, [2]"), "unbalanced array");
+        assert_eq!(error("a = [1, [2]]]"), "unbalanced array");
+        assert_eq!(error("a = [1, , 2]"), "empty array element");
+        assert_eq!(error("a = [1, x]"), "invalid value: x");
+    }
+
+    #[test]
+    fn tables_and_arrays() {
+        let tables = parse("a = 1\n[b.c]\nd = 2\n[[e]]\nf = 3\n[[e]]\nf = 4").unwrap();
+        let names: Vec<(&str, bool, usize)> =
+            tables.iter().map(|t| (t.name.as_str(), t.array, t.line)).collect();
+        assert_eq!(names, vec![("", false, 0), ("b.c", false, 2), ("e", true, 4), ("e", true, 6)]);
+        assert_eq!(tables[3].entries[0].value, Value::Integer(4));
+        assert_eq!(tables[3].entries[0].line, 7);
+    }
+
+    #[test]
+    fn duplicates() {
+        assert_eq!(error("a = 1\na = 2"), "key a is defined twice");
+        assert_eq!(error("[t]\na = 1\n[u]\n[t]"), "table t is defined twice");
+        assert!(parse("[[t]]\na = 1\n[[t]]\na = 2").is_ok());
+        assert!(parse("[t]\na = 1\n[u]\na = 2").is_ok());
+    }
+
+    #[test]
+    fn table_and_array_conflicts() {
+        assert_eq!(error("[t]\n[[t]]"), "table t is both a table and an array");
+        assert_eq!(error("[[t]]\n[t]"), "table t is both a table and an array");
+    }
+
+    #[test]
+    fn bad_lines() {
+        assert_eq!(error("[t"), "unclosed table header");
+        assert_eq!(error("[[t]"), "unclosed table header");
+        assert_eq!(error("[]"), "invalid table name: ");
+        assert_eq!(error("[a..b]"), "invalid table name: a..b");
+        assert_eq!(error("just words"), "expected key = value");
+        assert_eq!(error("a b = 1"), "invalid key: a b");
+        assert_eq!(error("= 1"), "invalid key: ");
+        assert_eq!(error("a ="), "missing value");
+    }
+
+    #[test]
+    fn unsupported_syntax_is_refused() {
+        assert_eq!(error("\"a b\" = 1"), "invalid key: \"a b\"");
+        assert_eq!(error("a.b = 1"), "invalid key: a.b");
+        assert_eq!(error("a = { b = 1 }"), "invalid value: { b = 1 }");
+        assert_eq!(error("a = 'b'"), "invalid value: 'b'");
+        assert_eq!(error("a = \"\"\"b\"\"\""), "unexpected text after string");
+        assert_eq!(error("a = [\n1,\n]"), "unclosed array");
+        assert_eq!(error("a = 0x10"), "invalid value: 0x10");
+        assert_eq!(error("a = 1979-05-27"), "invalid value: 1979-05-27");
+        assert_eq!(error("a = \"\\u00e9\""), "invalid escape in string");
+    }
+
+    #[test]
+    fn errors_name_the_line() {
+        let e = parse("# comment\n\na = 1\nb = nope").unwrap_err();
+        assert_eq!(e.line, 4);
+        assert_eq!(e.to_string(), "line 4: invalid value: nope");
+    }
+}
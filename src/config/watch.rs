@@ -0,0 +1,45 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::{Config, ConfigError};
+
+/// Watches a config file and reads it again whenever it changes
+pub struct Watcher {
+    /// File being watched
+    path: PathBuf,
+    /// When the file was last changed, as of the last check
+    modified: Option<SystemTime>,
+}
+
+impl Watcher {
+    /// Returns a watcher for `path`. The file as it is now counts as already
+    /// read
+    pub fn new(path: PathBuf) -> Self {
+        let modified = modified(&path);
+        Watcher { path, modified }
+    }
+
+    /// Returns the file being watched
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reads the file if it changed since the last check. Returns None when
+    /// it didn't change
+    pub fn poll(&mut self) -> Option<Result<Config, ConfigError>> {
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+
+        self.modified = modified;
+        // a file that is gone is left alone until it comes back
+        modified.map(|_| Config::load(&self.path))
+    }
+}
+
+/// Returns when a file was last changed, or None if that can't be found out
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
@@ -41,6 +41,12 @@ pub mod player_constants {
     pub const STARTSHOTS: u32 = 8;
     /// Starting health for player
     pub const STARTHEALTH: u32 = 30;
+    /// Speed of a dash in multiples of the velocity
+    pub const DASHSPEED: f64 = 3.0;
+    /// Length of a dash
+    pub const DASHTIME: f64 = 0.15;
+    /// Cooldown between dashes
+    pub const DASHCOOLDOWN: f64 = 1.0;
     /// Distance of shot amount bar from body
     pub const SBARDIST: f64 = 50.0;
     /// Distance of shot cooldown bar from body
@@ -68,7 +74,7 @@ pub mod boss_constants {
     /// Rotation multipleis
     pub const ROTMULT: f64 = 0.1;
     /// Total bullets it will shoot
-    pub const TOTBULL: f64 = 5.0;
+    pub const TOTBULL: u32 = 5;
 }
 
 /// Contains enemy constants
@@ -99,30 +105,119 @@ pub mod enemy_constants {
 
 /// Contains orb constants
 pub mod orb_constants {
-    /// Vel multiplier for rotation
-    pub const VEL: f64 = 0.01;
-    /// Used for reseting the angle
-    pub const EPSILON: f64 = 1e-10;
+    /// Rotation speed in multiples of pi per second
+    pub const VEL: f64 = 0.6;
     /// Cooldown for when it is active
     pub const COOLDOWN: f64 = 0.75;
 }
 
 /// Contains bullet constants
 pub mod bullet_constants {
-    /// Velocity of bullet
-    pub const VEL: f64 = 25.0;
+    /// Velocity of bullet in pixels per second
+    pub const VEL: f64 = 625.0;
     /// Sidelength of bullet
     pub const SIDELENGTH: f64 = 10.0;
 }
 
+/// Contains constants of the built in bot
+pub mod bot_constants {
+    /// Distance from the player that orbs are avoided within
+    pub const ORBDANGER: f64 = 120.0;
+    /// Seconds ahead that bullets are followed to see if they will hit
+    pub const LOOKAHEAD: f64 = 0.6;
+    /// Room left between the player and a bullet passing by
+    pub const CLEARANCE: f64 = 20.0;
+    /// Distance from the edge of the screen the bot tries to stay away from
+    pub const EDGE: f64 = 60.0;
+    /// Distance to the target the bot turns back at
+    pub const NEAR: f64 = 110.0;
+    /// Distance to the target the bot goes back on the attack at
+    pub const FAR: f64 = 250.0;
+    /// How much dodging outweighs attacking
+    pub const DODGE: f64 = 2.0;
+    /// Distance ahead of the player the bot moves towards
+    pub const STRIDE: f64 = 100.0;
+}
+
 /// Contains game constants
 pub mod game_constants {
     /// FPS for game
     pub const FPS: u64 = 60;
+    /// Length of a simulation step in seconds. Does not depend on the FPS
+    pub const TIMESTEP: f64 = 1.0 / 60.0;
+    /// Most steps taken in one update. Stops a slow machine from falling
+    /// further and further behind
+    pub const MAXSTEPS: u32 = 10;
     /// Font size for score
     pub const FONTSIZE: u32 = 15;
     /// Health added for boss kill
     pub const BOSSKILL: u32 = 2;
     /// Health added for enemy kill
     pub const ENEMYKILL: u32 = 1;
+    /// Config file read when no other one is given
+    pub const CONFIG: &str = "config.toml";
+    /// Settings file read when no other one is given
+    pub const SETTINGS: &str = "settings.toml";
+    /// Size of the cells of the collision grid. About the size of the
+    /// biggest things in the game
+    pub const GRIDCELL: f64 = 100.0;
+    /// Most columns or rows of the collision grid. On bigger screens the
+    /// last ones hold everything past them
+    pub const MAXGRIDCELLS: i64 = 1024;
+    /// Seconds between checks of the config file for changes
+    pub const CONFIGCHECK: f64 = 0.5;
+    /// File used for quicksaves
+    pub const QUICKSAVE: &str = "quicksave.txt";
+    /// Seconds the banner of a new level is shown before it starts
+    pub const LEVELPAUSE: f64 = 1.5;
+    /// Font size for the titles of screens
+    pub const TITLESIZE: u32 = 50;
+    /// File in the data directory that holds the high scores
+    pub const HIGHSCORES: &str = "highscores.txt";
+    /// Most runs kept in the high-score table
+    pub const MAXSCORES: usize = 10;
+    /// Most characters in a name in the high-score table
+    pub const NAMELENGTH: usize = 12;
+}
+
+/// Contains achievement constants
+pub mod achievement_constants {
+    /// File in the data directory that holds the unlocked achievements
+    pub const ACHIEVEMENTS: &str = "achievements.txt";
+    /// Level to reach for the veteran achievement
+    pub const VETERANLEVEL: u32 = 10;
+    /// Seconds to stay alive for the survivor achievement
+    pub const SURVIVORTIME: f64 = 300.0;
+    /// Kills a level has to take for the marksman achievement, so a level
+    /// of a single enemy doesn't count
+    pub const MARKSMANKILLS: u32 = 2;
+    /// Seconds an unlocked achievement is shown in the HUD
+    pub const TOASTTIME: f64 = 4.0;
+}
+
+/// Contains constants of the difficulty director
+pub mod director_constants {
+    /// Whether the director adjusts the game
+    pub const ENABLED: bool = false;
+    /// Seconds between adjustments
+    pub const INTERVAL: f64 = 5.0;
+    /// Seconds of recent play the director looks at
+    pub const WINDOW: f64 = 30.0;
+    /// How far the rating moves in one adjustment
+    pub const STEP: f64 = 0.1;
+    /// Health lost per second above which the game is made easier
+    pub const DAMAGERATE: f64 = 0.5;
+    /// Kills per second above which the game is made harder
+    pub const KILLRATE: f64 = 0.5;
+    /// Fraction of the starting health below which the game is made easier
+    pub const LOWHEALTH: f64 = 0.3;
+    /// Reinforcements per kill added or taken away at the highest or lowest
+    /// rating
+    pub const SPAWNRANGE: u32 = 1;
+    /// Fraction enemy and boss cooldowns shrink or grow by at the highest or
+    /// lowest rating
+    pub const FIRERANGE: f64 = 0.3;
+    /// Bosses added to or taken from every boss spawn at the highest or
+    /// lowest rating
+    pub const BOSSRANGE: u32 = 1;
 }
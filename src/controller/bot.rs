@@ -0,0 +1,154 @@
+//! Computer players, so games can be played without anyone at the mouse.
+
+use std::f64;
+
+use constants::bot_constants::*;
+use simulation::{Input, Simulation};
+use vector::Vector;
+
+use super::Controller;
+
+/// How a bot plays
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Strategy {
+    /// Makes runs at the closest enemy or boss, shooting at it on the way in
+    /// and out, and dodges bullets and orbs
+    Hunter,
+    /// Stays where it started and keeps shooting
+    Idle,
+}
+
+/// Plays the game by giving the simulation input every step
+pub struct Bot {
+    strategy: Strategy,
+    /// Whether the bot is backing off after a run at its target
+    retreating: bool,
+}
+
+impl Bot {
+    /// Returns a bot that plays with `strategy`
+    pub fn new(strategy: Strategy) -> Self {
+        Bot {
+            strategy,
+            retreating: false,
+        }
+    }
+
+    /// Returns the strategy of the bot
+    pub fn get_strategy(&self) -> Strategy {
+        self.strategy
+    }
+}
+
+impl Controller for Bot {
+    fn inputs(&mut self, sim: &Simulation, input: &mut Vec<Input>) {
+        if let Strategy::Idle = self.strategy {
+            input.push(Input::Shoot(true));
+            return;
+        }
+
+        let player = sim.get_player().get_pos();
+        let target = closest_target(sim);
+
+        // make runs at the target and back off once it gets close to its orb
+        let mut attack = Vector::default();
+        if let Some(target) = target {
+            let dist = player.dist(&target);
+            if dist < NEAR {
+                self.retreating = true;
+            } else if dist > FAR {
+                self.retreating = false;
+            }
+            attack = (target - player).normalize();
+            if self.retreating {
+                attack = -attack;
+            }
+            input.push(Input::Aim(target.x, target.y));
+        } else {
+            self.retreating = false;
+        }
+        input.push(Input::Shoot(target.is_some()));
+
+        let dodge = dodge(sim);
+        let dir = attack + dodge * DODGE;
+        let dest = match target {
+            // nothing to dodge, aim right at the target
+            Some(target) if dodge == Vector::default() && !self.retreating => target,
+            _ if dir.length() > 0.0 => player + dir.normalize() * STRIDE,
+            _ => player,
+        };
+
+        let dims = sim.get_dimensions();
+        let dest = dest.clamp_to_rect(&Vector::default(), &Vector::new(dims[0], dims[1]));
+        input.push(Input::Target(dest.x, dest.y));
+    }
+}
+
+/// Returns the position of the enemy or boss closest to the player
+fn closest_target(sim: &Simulation) -> Option<Vector> {
+    let player = sim.get_player().get_pos();
+    let enemies = sim.get_enemies().iter().map(|e| e.get_pos());
+    let bosses = sim.get_bosses().iter().map(|b| b.get_pos());
+
+    enemies.chain(bosses).fold(None, |closest: Option<Vector>, pos| match closest {
+        Some(c) if c.dist(&player) <= pos.dist(&player) => Some(c),
+        _ => Some(pos),
+    })
+}
+
+/// Returns the way the player should move to stay clear of bullets about to
+/// hit it, orbs and the edges of the screen. The more urgent the danger the
+/// longer the vector
+fn dodge(sim: &Simulation) -> Vector {
+    let config = sim.get_config();
+    let player = sim.get_player().get_pos();
+    let reach = config.player.diameter / 2.0 + config.bullet.side_length + CLEARANCE;
+    let mut push = Vector::default();
+
+    for bullet in sim.get_enemy_bullets() {
+        let step = bullet.get_pos() - bullet.get_prev_pos();
+        if step.length_squared() == 0.0 {
+            continue;
+        }
+
+        // where the bullet comes closest to the player
+        let vel = step.normalize() * config.bullet.velocity;
+        let rel = bullet.get_pos() - player;
+        let time = (-rel.dot(&vel) / vel.length_squared()).clamp(0.0, LOOKAHEAD);
+        let closest = rel + vel * time;
+        let miss = closest.length();
+        if miss >= reach {
+            continue;
+        }
+
+        // step out of its way, sideways if it is coming straight at us
+        let away = if miss > 0.0 {
+            -closest / miss
+        } else {
+            step.normalize().rotate(f64::consts::FRAC_PI_2)
+        };
+        push += away * ((reach - miss) / reach * (1.0 - time / LOOKAHEAD));
+    }
+
+    for enemy in sim.get_enemies() {
+        if !enemy.get_orb_active() {
+            continue;
+        }
+        let rel = enemy.get_orb_pos() - player;
+        let dist = rel.length();
+        if dist > 0.0 && dist < ORBDANGER {
+            push -= rel / dist * (1.0 - dist / ORBDANGER);
+        }
+    }
+
+    // the edges leave no room to dodge
+    let dims = sim.get_dimensions();
+    let edge = |pos: f64, size: f64| if pos < EDGE {
+        1.0 - pos / EDGE
+    } else if pos > size - EDGE {
+        (size - pos) / EDGE - 1.0
+    } else {
+        0.0
+    };
+    push + Vector::new(edge(player.x, dims[0]), edge(player.y, dims[1])) / DODGE
+}
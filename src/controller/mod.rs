@@ -0,0 +1,21 @@
+//! Whatever steers the player: a person at the mouse, a replay or a bot.
+
+pub mod bot;
+pub mod mouse;
+
+use simulation::{Input, Simulation};
+
+/// Decides where the player moves and whether it shoots
+pub trait Controller {
+    /// Adds the input for the next step to `input`
+    ///
+    /// # Arguments
+    ///
+    /// * `sim` - The game as it is before the step
+    /// * `input` - Input for the step. May already hold input from the window
+    fn inputs(&mut self, sim: &Simulation, input: &mut Vec<Input>);
+
+    /// Hands the controller input from the mouse and keyboard. Controllers
+    /// that aren't driven by a person ignore it
+    fn window_input(&mut self, _input: Input) {}
+}
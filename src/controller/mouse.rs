@@ -0,0 +1,36 @@
+use std::mem;
+
+use simulation::{Input, Simulation};
+
+use super::Controller;
+
+/// Steers the player with the mouse and keyboard. Passes on the input the
+/// window gave it since the last step. Of the places to go or aim at and the
+/// directions to move in only the latest is kept, so the ones given while
+/// the game is paused or on the title screen don't pile up
+#[derive(Default)]
+pub struct Mouse {
+    /// Input received since the last step
+    pending: Vec<Input>,
+}
+
+impl Mouse {
+    /// Returns a controller without pending input
+    pub fn new() -> Self {
+        Mouse { pending: Vec::new() }
+    }
+}
+
+impl Controller for Mouse {
+    fn inputs(&mut self, _sim: &Simulation, input: &mut Vec<Input>) {
+        input.append(&mut self.pending);
+    }
+
+    fn window_input(&mut self, input: Input) {
+        if let Input::Target(..) | Input::Aim(..) | Input::Move(..) = input {
+            let kind = mem::discriminant(&input);
+            self.pending.retain(|i| mem::discriminant(i) != kind);
+        }
+        self.pending.push(input);
+    }
+}
@@ -0,0 +1,33 @@
+//! Files kept between games in the user's data directory, like the high
+//! scores and the achievements.
+
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use dirs;
+
+/// Returns where a file named `name` is kept in the data directory of the
+/// user. `None` when the system doesn't have one
+pub fn get_path(name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("hit_n_run").join(name))
+}
+
+/// Writes `text` to a file, creating its directory if needed. The old file
+/// is only replaced once the new one is written in full, so a crash can't
+/// leave half a file behind
+pub fn replace_file(path: &Path, text: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let temp = path.with_extension("tmp");
+    {
+        let mut out = File::create(&temp)?;
+        out.write_all(text.as_bytes())?;
+        out.sync_all()?;
+    }
+    fs::rename(temp, path)
+}
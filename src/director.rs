@@ -0,0 +1,187 @@
+//! Makes the game harder or easier depending on how the player is doing,
+//! kept by the simulation when it is enabled in the config.
+//!
+//! Play is cut into samples of `interval` seconds. At the end of each sample
+//! the director looks at the damage taken and kills made in the last
+//! `window` seconds and the health of the player, and moves its rating a
+//! step up or down. The rating goes from -1 to 1 and scales reinforcements,
+//! enemy fire and bosses within the ranges of the config.
+
+use std::collections::VecDeque;
+
+use config::{Config, DirectorConfig};
+use events::Event;
+use snapshot::{Reader, Snapshot, SnapshotError, Writer};
+
+/// What happened during a sample of play
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct Sample {
+    /// Health the player lost
+    damage: u32,
+    /// Enemies and bosses killed
+    kills: u32,
+}
+
+/// Watches the player and rates how hard the game should be
+#[derive(Clone, Debug, PartialEq)]
+pub struct Director {
+    /// How hard the game is made, from -1 for easiest to 1 for hardest
+    rating: f64,
+    /// Seconds into the current sample
+    time: f64,
+    /// The sample being played
+    current: Sample,
+    /// Finished samples, the latest last
+    samples: VecDeque<Sample>,
+}
+
+impl Default for Director {
+    fn default() -> Self {
+        Director::new()
+    }
+}
+
+impl Director {
+    /// Returns a director that leaves the game as it is
+    pub fn new() -> Self {
+        Director {
+            rating: 0.0,
+            time: 0.0,
+            current: Sample::default(),
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Counts an event of the simulation
+    pub fn record(&mut self, event: &Event) {
+        match *event {
+            Event::PlayerDamaged { .. } => self.current.damage += 1,
+            Event::EnemyKilled { .. } | Event::BossKilled { .. } => self.current.kills += 1,
+            _ => {}
+        }
+    }
+
+    /// Counts `dt` seconds of play and adjusts the rating at the end of a
+    /// sample. Returns the new rating if it changed. A disabled director
+    /// forgets what it saw and goes back to a rating of 0
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Seconds that were played
+    /// * `health` - Health of the player as a fraction of its starting health
+    /// * `config` - Tuning values of the director
+    pub fn update(&mut self, dt: f64, health: f64, config: &DirectorConfig) -> Option<f64> {
+        if !config.enabled {
+            let changed = self.rating != 0.0;
+            *self = Director::new();
+            return if changed { Some(0.0) } else { None };
+        }
+
+        self.time += dt;
+        if self.time < config.interval {
+            return None;
+        }
+        self.time -= config.interval;
+        self.samples.push_back(self.current);
+        self.current = Sample::default();
+
+        // only the samples inside the window count
+        let kept = (config.window / config.interval).ceil().max(1.0) as usize;
+        while self.samples.len() > kept {
+            self.samples.pop_front();
+        }
+
+        let seconds = self.samples.len() as f64 * config.interval;
+        let damage = self.samples.iter().map(|s| s.damage).sum::<u32>() as f64 / seconds;
+        let kills = self.samples.iter().map(|s| s.kills).sum::<u32>() as f64 / seconds;
+
+        let change = if health < config.low_health || damage > config.damage_rate {
+            -config.step
+        } else if kills > config.kill_rate {
+            config.step
+        } else {
+            0.0
+        };
+        let rating = (self.rating + change).clamp(-1.0, 1.0);
+        if rating == self.rating {
+            return None;
+        }
+        self.rating = rating;
+        Some(rating)
+    }
+
+    /// Returns how hard the game is made, from -1 for easiest to 1 for
+    /// hardest
+    pub fn get_rating(&self) -> f64 {
+        self.rating
+    }
+
+    /// Returns the enemies added for a kill instead of `reinforcements`
+    pub fn get_reinforcements(&self, reinforcements: u32, config: &DirectorConfig) -> u32 {
+        adjust(reinforcements, self.rating * f64::from(config.spawn_range), 0)
+    }
+
+    /// Returns the bosses spawned instead of `count`. A spawn that has
+    /// bosses keeps at least one
+    pub fn get_bosses(&self, count: u32, config: &DirectorConfig) -> u32 {
+        if count == 0 {
+            return 0;
+        }
+        adjust(count, self.rating * f64::from(config.boss_range), 1)
+    }
+
+    /// Returns how much the cooldowns of enemies and bosses are scaled by
+    pub fn get_cooldown_scale(&self, config: &DirectorConfig) -> f64 {
+        1.0 - self.rating * config.fire_range
+    }
+
+    /// Returns `config` with the fire rate of enemies and bosses adjusted
+    pub fn apply(&self, config: &Config) -> Config {
+        let scale = self.get_cooldown_scale(&config.director);
+        let mut config = config.clone();
+        config.enemy.cooldown *= scale;
+        config.boss.cooldown *= scale;
+        config
+    }
+}
+
+/// Returns a whole number moved by `offset`, rounded and not below `min`
+fn adjust(value: u32, offset: f64, min: u32) -> u32 {
+    (f64::from(value) + offset).round().max(f64::from(min)) as u32
+}
+
+impl Snapshot for Director {
+    fn save(&self, w: &mut Writer) {
+        w.tag("director");
+        w.value(self.rating);
+        w.value(self.time);
+        w.value(self.current.damage);
+        w.value(self.current.kills);
+        w.value(self.samples.len());
+        for sample in &self.samples {
+            w.value(sample.damage);
+            w.value(sample.kills);
+        }
+    }
+
+    fn load(r: &mut Reader) -> Result<Self, SnapshotError> {
+        r.tag("director")?;
+        let mut director = Director {
+            rating: r.value()?,
+            time: r.value()?,
+            current: Sample {
+                damage: r.value()?,
+                kills: r.value()?,
+            },
+            samples: VecDeque::new(),
+        };
+        let len: usize = r.value()?;
+        for _ in 0..len {
+            director.samples.push_back(Sample {
+                damage: r.value()?,
+                kills: r.value()?,
+            });
+        }
+        Ok(director)
+    }
+}
@@ -0,0 +1,90 @@
+//! Things that happen in the game, and the bus that hands them to whoever
+//! wants to know about them.
+
+use vector::Vector;
+
+/// What damaged the player
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DamageSource {
+    /// A bullet shot by an enemy or a boss
+    Bullet,
+    /// The orb circling an enemy
+    Orb,
+}
+
+/// Something that happened during a step of the simulation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    /// The player shot a bullet
+    PlayerFired,
+    /// The player started a dash
+    PlayerDashed,
+    /// An enemy was hit but survived
+    EnemyHit { pos: Vector },
+    /// An enemy died
+    EnemyKilled { pos: Vector },
+    /// A boss was hit but survived
+    BossHit { pos: Vector },
+    /// A boss died
+    BossKilled { pos: Vector },
+    /// The player lost health
+    PlayerDamaged { source: DamageSource },
+    /// Enough kills were made to move on to `level`
+    LevelAdvanced { level: u32 },
+    /// The player died
+    GameOver { score: u32, level: u32 },
+    /// A new run started with `seed`
+    GameReset { seed: u64 },
+    /// The director changed how hard the game is to `rating`, from -1 for
+    /// easiest to 1 for hardest
+    DirectorAdjusted { rating: f64 },
+}
+
+/// Something that wants to know what happens in the game
+pub trait Observer {
+    /// Called for every event, in the order they happened
+    ///
+    /// # Arguments
+    ///
+    /// * `tick` - Step of the simulation the event happened on
+    /// * `event` - What happened
+    fn on_event(&mut self, tick: u64, event: &Event);
+}
+
+/// Hands events to every observer that subscribed to it
+#[derive(Default)]
+pub struct EventBus {
+    /// Observers in the order they subscribed
+    observers: Vec<Box<dyn Observer>>,
+}
+
+impl EventBus {
+    /// Returns a bus without observers
+    pub fn new() -> Self {
+        EventBus { observers: Vec::new() }
+    }
+
+    /// Adds an observer. Observers get each event in the order they
+    /// subscribed
+    pub fn subscribe(&mut self, observer: Box<dyn Observer>) {
+        self.observers.push(observer);
+    }
+
+    /// Hands the events of a step to every observer
+    pub fn publish(&mut self, tick: u64, events: &[Event]) {
+        for event in events {
+            for observer in &mut self.observers {
+                observer.on_event(tick, event);
+            }
+        }
+    }
+}
+
+/// Prints every event
+pub struct Logger;
+
+impl Observer for Logger {
+    fn on_event(&mut self, tick: u64, event: &Event) {
+        println!("{:>8} {:?}", tick, event);
+    }
+}
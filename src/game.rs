@@ -2,45 +2,87 @@ extern crate piston;
 extern crate graphics;
 extern crate glutin_window;
 extern crate opengl_graphics;
-extern crate rand;
-extern crate find_folder;
 
 use piston::event_loop::*;
 use piston::input::*;
 use opengl_graphics::GlGraphics;
 use opengl_graphics::glyph_cache::GlyphCache;
-use rand::Rng;
 use glutin_window::GlutinWindow as Window;
-
+use std::fs;
+use std::mem;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use achievements::Achievements;
+use config::Config;
+use config::difficulty::Difficulty;
+use config::watch::Watcher;
+use constants::achievement_constants::TOASTTIME;
 use constants::game_constants::*;
-use constants::color::*;
-use models::enemy::Enemy;
-use models::player::Player;
-use models::boss::Boss;
-use weapons::bullet::Bullet;
-
-/// Contains Game State
+use controller::Controller;
+use controller::mouse::Mouse;
+use events::{Event, EventBus, Observer};
+use highscores::{Entry, HighScores};
+use render::Renderer;
+use replay::{Playback, Recorder, Replay};
+use script::Script;
+use settings::{Action, Bindings, Movement, Settings};
+use simulation::{Input, Simulation};
+use snapshot;
+use state::State;
+
+/// Piston front-end for the simulation. Turns window events into input and
+/// draws the simulation.
 pub struct Game {
-    /// User controlled player. controlled with mouse and keyboard
-    player: Player,
-    /// Enemies that have to be killed
-    enemies: Vec<Enemy>,
-    /// Bullets shot by the player
-    player_bullets: Vec<Bullet>,
-    /// Bullets shot by the enemy
-    enemy_bullets: Vec<Bullet>,
-    /// Dimensions of the game screen [width,height]
-    dimensions: [f64; 2],
-    /// Score of the game.
-    score: u32,
-    /// Current level of the game.
-    level: u32,
-    /// Kills made during this level
-    current_kills: u32,
-    /// But did you die tho?
-    game_over: bool,
-    /// Bosses
-    bosses: Vec<Boss>,
+    /// State of the game
+    sim: Simulation,
+    /// Input from the window that doesn't steer the player, like resizes
+    input: Vec<Input>,
+    /// Steers the player
+    controller: Box<dyn Controller>,
+    /// Time that has passed but hasn't been simulated yet
+    accumulator: f64,
+    /// Records the input when the game is being recorded
+    recorder: Option<Recorder>,
+    /// Whether a replay is being played back. The window is ignored then
+    replaying: bool,
+    /// Watches the config file for changes
+    watcher: Option<Watcher>,
+    /// Time since the config file was last checked
+    config_timer: f64,
+    /// Hands the events of every step to the observers
+    bus: EventBus,
+    /// Config as it was given, before the difficulty scaled it
+    base_config: Config,
+    /// How hard the game is
+    difficulty: Difficulty,
+    /// What the keys and mouse buttons do
+    bindings: Bindings,
+    /// Whether the player follows the mouse or is moved with the keys
+    movement: Movement,
+    /// Movement buttons that are held down
+    held: Vec<Button>,
+    /// Screen the game is on. The simulation only runs while playing
+    state: State,
+    /// The best runs so far
+    highscores: HighScores,
+    /// File the high scores are saved to. They aren't kept without one
+    highscores_path: Option<PathBuf>,
+    /// Whether runs can make it into the high-score table. Only runs played
+    /// by a person can
+    scoring: bool,
+    /// Name being typed for a run that made it into the high-score table
+    name: Option<String>,
+    /// Place of the last run that made it into the high-score table
+    place: Option<usize>,
+    /// Unlocked achievements, and how the current run is doing on the others
+    achievements: Achievements,
+    /// File the achievements are saved to. They aren't kept without one
+    achievements_path: Option<PathBuf>,
+    /// Messages shown in the HUD, with the seconds they have left
+    toasts: Vec<(String, f64)>,
+    /// Whether the debug overlay is shown
+    debug: bool,
 }
 
 impl Game {
@@ -50,292 +92,599 @@ impl Game {
     ///
     /// * `width` - An integer that holds the width of the screen
     /// * `height` - An integer that holds the height of the screen
-    pub fn new(width: f64, height: f64) -> Self {
+    /// * `seed` - Seed for the random number generator
+    /// * `config` - Gameplay values
+    /// * `script` - Levels of the game
+    pub fn new(width: f64, height: f64, seed: u64, config: Config, script: Script) -> Self {
         Game {
-            // player starts out at center of screen
-            player: Player::new(width / 2.0, height / 2.0),
-            player_bullets: Vec::<Bullet>::new(),
-            enemy_bullets: Vec::<Bullet>::new(),
-            enemies: Vec::<Enemy>::new(),
-            dimensions: [width, height],
-            score: 0,
-            level: 1,
-            current_kills: 0,
-            game_over: false,
-            bosses: Vec::<Boss>::new(),
+            sim: Simulation::new(width, height, seed, config.clone(), script),
+            input: Vec::new(),
+            controller: Box::new(Mouse::new()),
+            accumulator: 0.0,
+            recorder: None,
+            replaying: false,
+            watcher: None,
+            config_timer: 0.0,
+            bus: EventBus::new(),
+            base_config: config,
+            difficulty: Difficulty::Normal,
+            bindings: Bindings::default(),
+            movement: Movement::default(),
+            held: Vec::new(),
+            state: State::Title,
+            highscores: HighScores::new(),
+            highscores_path: None,
+            scoring: true,
+            name: None,
+            place: None,
+            achievements: Achievements::new(),
+            achievements_path: None,
+            toasts: Vec::new(),
+            debug: false,
         }
+    }
 
+    /// Returns a game that plays back a replay on the difficulty it was
+    /// recorded on. Input from the window is ignored. The replay only plays
+    /// back the same game with the config and script it was recorded with.
+    pub fn from_replay(replay: Replay, config: Config, script: Script) -> Self {
+        let mut game = Game::new(replay.dimensions[0],
+                                 replay.dimensions[1],
+                                 replay.seed,
+                                 config,
+                                 script);
+        game.set_difficulty(replay.difficulty);
+        game.controller = Box::new(Playback::new(replay));
+        game.replaying = true;
+        game.scoring = false;
+        game.state = State::Playing;
+        game
     }
 
-    /// Updates all the things in the game
-    ///
-    /// # Arguments
-    ///
-    /// * `args` - An event that contains update information
-    fn on_update(&mut self, args: &UpdateArgs) {
-        // update player, check if a bullet was shot
-        match self.player.update(args, &self.dimensions) {
-            Some(bullet) => self.player_bullets.push(bullet),
-            None => {}
+    /// Records every input of the game. The replay is written to `path` when
+    /// the game is closed.
+    pub fn record(&mut self, path: PathBuf) {
+        let dimensions = self.sim.get_dimensions();
+        let mut recorder = Recorder::new(path, self.sim.get_seed(), dimensions[0], dimensions[1]);
+        recorder.set_difficulty(self.difficulty);
+        recorder.set_sources(&self.base_config, self.sim.get_script());
+        self.recorder = Some(recorder);
+    }
+
+    /// Reloads the config whenever the file at `path` changes
+    pub fn watch_config(&mut self, path: PathBuf) {
+        self.watcher = Some(Watcher::new(path));
+    }
+
+    /// Starts the game over on `difficulty`, with the same seed. Meant to be
+    /// called before the first run starts
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+        let dimensions = self.sim.get_dimensions();
+        self.sim = Simulation::new(dimensions[0],
+                                   dimensions[1],
+                                   self.sim.get_seed(),
+                                   difficulty.apply(&self.base_config),
+                                   self.sim.get_script().clone());
+        self.sim.set_difficulty(difficulty);
+
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.set_difficulty(difficulty);
         }
+    }
+
+    /// Hands the player over to `controller`, like a bot. Input from the
+    /// window that doesn't steer the player, like resets, still works
+    pub fn set_controller(&mut self, controller: Box<dyn Controller>) {
+        self.controller = controller;
+        self.scoring = false;
+    }
 
-        // check for hit
-        for bullet in &mut self.enemy_bullets {
-            bullet.update(args, &self.dimensions);
-            self.player.hit(bullet);
+    /// Reads the high-score table at `path` and saves it there whenever a
+    /// run makes it in. Unreadable lines are skipped and the file they were
+    /// in is backed up
+    pub fn keep_highscores(&mut self, path: PathBuf) {
+        if let Some(highscores) = load_kept(&path, "high scores", |p| HighScores::load(p)) {
+            self.highscores = highscores;
+            self.highscores_path = Some(path);
         }
+    }
 
-        // check if we should end the game
-        if !self.player.get_alive() {
-            self.update_game_over(true);
+    /// Reads the achievements unlocked at `path` and saves them there
+    /// whenever one is unlocked. Only runs played by a person unlock them
+    pub fn keep_achievements(&mut self, path: PathBuf) {
+        if let Some(achievements) = load_kept(&path, "achievements", |p| Achievements::load(p)) {
+            self.achievements = achievements;
+            self.achievements_path = Some(path);
         }
+    }
 
-        for enemy in &mut self.enemies {
-            // update enemy, check for bullet
-            let shot = enemy.update(args,
-                                    self.player.get_x(),
-                                    self.player.get_y(),
-                                    &self.dimensions);
-            match shot {
-                Some(bullet) => self.enemy_bullets.push(bullet),
-                None => {}
-            }
+    /// Changes what the keys and mouse buttons do and how the player is
+    /// moved
+    pub fn set_settings(&mut self, settings: Settings) {
+        self.bindings = settings.bindings;
+        self.movement = settings.movement;
+        self.held.clear();
 
-            // check if the player collided with the enemy
-            self.player.collide(enemy);
+        // stand still until a movement key is pressed
+        if let Movement::Keyboard = self.movement {
+            self.controller.window_input(Input::Move(0.0, 0.0));
         }
+    }
 
-        for boss in &mut self.bosses {
-            // update the bosses
-            let shots = boss.update(args);
+    /// Tells `observer` about everything that happens in the game from now on
+    pub fn subscribe(&mut self, observer: Box<dyn Observer>) {
+        self.bus.subscribe(observer);
+    }
 
-            match shots {
-                Some(mut vec) => self.enemy_bullets.append(&mut vec),
-                None => {}
-            }
+    /// Returns the dimensions of the game screen
+    pub fn get_dimensions(&self) -> [f64; 2] {
+        self.sim.get_dimensions()
+    }
+
+    /// Steps the simulation in fixed steps for the time that has passed
+    /// since the last update
+    fn on_update(&mut self, args: &UpdateArgs) {
+        // check the config file every now and then
+        self.config_timer += args.dt;
+        if self.config_timer >= CONFIGCHECK {
+            self.config_timer = 0.0;
+            self.reload_config();
+        }
+
+        // toasts fade even while the game is frozen
+        for toast in &mut self.toasts {
+            toast.1 -= args.dt;
         }
+        self.toasts.retain(|t| t.1 > 0.0);
 
-        // check if an enemy or a boss was hit
-        for bullet in &mut self.player_bullets {
-            bullet.update(args, &self.dimensions);
-            for enemy in &mut self.enemies {
-                enemy.hit(bullet);
+        self.state = self.state.update(args.dt);
+        if !self.state.is_running() {
+            return;
+        }
+        self.accumulator += args.dt;
+
+        let mut steps = 0;
+        while self.accumulator >= TIMESTEP {
+            // a new level or the end of the game freezes everything
+            if !self.state.is_running() {
+                self.accumulator %= TIMESTEP;
+                break;
             }
-            for boss in &mut self.bosses {
-                boss.hit(bullet);
+
+            // give up on catching up if we are too far behind
+            if steps == MAXSTEPS {
+                self.accumulator = 0.0;
+                break;
             }
+
+            self.step();
+            self.accumulator -= TIMESTEP;
+            steps += 1;
         }
+    }
 
-        // remove bullets that are no longer alive
-        self.player_bullets.retain(|e| e.get_alive());
-        self.enemy_bullets.retain(|e| e.get_alive());
-
-        // how many enemies we will add (2 times the amount of kills)
-        let mut enemies_to_add = 0;
-
-        // used so that local borrows die
-        {
-            // borrow before closure
-            let player = &mut self.player;
-            let score = &mut self.score;
-            let kills = &mut self.current_kills;
-
-            // check if enemies are alive
-            self.enemies.retain(|e| if e.get_alive() {
-                true
-            } else {
-                *score += 1;
-                *kills += 1;
-                enemies_to_add += 2;
-                player.increase_health(ENEMYKILL);
-                false
-            });
-
-        }
-
-        // used so that local borrows die
-        {
-            // borrow before closure
-            let player = &mut self.player;
-            let score = &mut self.score;
-
-            // remove dead bosses
-            self.bosses.retain(|e| if e.get_alive() {
-                true
-            } else {
-                player.increase_health(BOSSKILL);
-                *score += 2;
-                false
-            });
-
-        }
-
-        // check if we have passed a level
-        if self.new_level() {
-            self.enemies.clear();
-            self.add_enemy();
-            let mut x = 0;
-
-            // add bosses
-            while x < self.level {
-                self.add_boss();
-                x += 1;
-            }
+    /// Steps the simulation once with the input received since the last step
+    fn step(&mut self) {
+        let tick = self.sim.get_tick();
+
+        let mut input = mem::take(&mut self.input);
+        self.controller.inputs(&self.sim, &mut input);
+
+        // a replay that can't be written is given up on rather than ending
+        // the game
+        let recorded = match self.recorder {
+            Some(ref mut recorder) => recorder.record(tick, &input),
+            None => Ok(()),
+        };
+        if let Err(e) = recorded {
+            eprintln!("could not write replay, recording stopped: {}", e);
+            self.recorder = None;
+        }
 
-            // update level information
-            self.update_level();
-        } else {
-            let mut x = 0;
-            while x < enemies_to_add {
-                self.add_enemy();
-                x += 1;
+        self.sim.step(TIMESTEP, &input);
+        self.bus.publish(tick, self.sim.get_events());
+        for event in self.sim.get_events() {
+            self.state = self.state.on_event(event);
+            if let Event::GameOver { score, .. } = *event {
+                self.place = None;
+                if self.scoring && self.highscores_path.is_some() &&
+                   self.highscores.qualifies(score) {
+                    self.name = Some(String::new());
+                }
             }
         }
 
+        if self.scoring {
+            self.check_achievements(tick);
+        }
 
+        // keep the buffer for the next step
+        input.clear();
+        self.input = input;
     }
 
-    /// Draws the gameboard
-    fn on_draw(&self, args: &RenderArgs, gl: &mut GlGraphics, glyph_cache: &mut GlyphCache) {
-        use graphics::*;
-
-        // draw viewport
-        gl.draw(args.viewport(), |c, gl| {
-            // clear the screen
-            clear(BLACK, gl);
+    /// Hands the events of the last step to the achievements and shows the
+    /// ones that got unlocked
+    fn check_achievements(&mut self, tick: u64) {
+        for event in self.sim.get_events() {
+            self.achievements.on_event(tick, event);
+        }
+        self.achievements.on_step(&self.sim);
 
-            // draw enemy bullets
-            for bullet in &self.enemy_bullets {
-                bullet.draw(c, gl);
+        let unlocked = self.achievements.take_new();
+        if unlocked.is_empty() {
+            return;
+        }
+        for achievement in unlocked {
+            self.toasts.push((format!("Achievement unlocked: {} - {}",
+                                      achievement.get_name(),
+                                      achievement.get_description()),
+                              TOASTTIME));
+        }
+        if let Some(ref path) = self.achievements_path {
+            if let Err(e) = self.achievements.save(path) {
+                eprintln!("could not save achievements: {}", e);
             }
+        }
+    }
 
-            // draw friendly bullets
-            for bullet in &self.player_bullets {
-                bullet.draw(c, gl);
+    /// Swaps in the config if its file changed. A config that can't be read
+    /// is reported and the old one is kept
+    fn reload_config(&mut self) {
+        let result = match self.watcher {
+            Some(ref mut watcher) => {
+                match watcher.poll() {
+                    Some(result) => result,
+                    None => return,
+                }
             }
+            None => return,
+        };
 
-            // draw enemies
-            for enemy in &self.enemies {
-                enemy.draw(c, gl, glyph_cache);
-            }
+        // a replay can't reproduce a game whose config changed halfway through
+        if self.recorder.is_some() || self.replaying {
+            eprintln!("config reloading is disabled while recording or replaying");
+            return;
+        }
 
-            // draw bosses
-            for boss in &self.bosses {
-                boss.draw(c, gl, glyph_cache);
+        match result {
+            Ok(config) => {
+                self.sim.set_config(self.difficulty.apply(&config));
+                self.base_config = config;
+                println!("config reloaded");
             }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
 
-            // draw player
-            self.player.draw(c, gl);
-
-            // display score and health
-            text(WHITE,
-                 FONTSIZE,
-                 format!("Score: {} | Health: {} | Level: {} | Level Kills: {}",
-                         self.get_score(),
-                         self.player.get_health(),
-                         self.get_level(),
-                         self.get_kills())
-                     .as_str(),
-                 glyph_cache,
-                 c.transform.trans(15.0, 15.0),
-                 gl);
-
-            // draw reset screen
-            if !self.player.get_alive() {
-                text(ORANGE,
-                     50,
-                     format!("RIP. R to Reset").as_str(),
-                     glyph_cache,
-                     c.transform.trans(15.0, 75.0),
-                     gl);
+    /// Draws the gameboard
+    fn on_draw(&self,
+               args: &RenderArgs,
+               renderer: &Renderer,
+               gl: &mut GlGraphics,
+               glyph_cache: &mut GlyphCache) {
+        // how far we are between the last step and the next one
+        let alpha = self.accumulator / TIMESTEP;
+
+        let button = |action| self.bindings.get_button_name(action);
+        gl.draw(args.viewport(), |c, gl| {
+            if let State::Title = self.state {
+                let lines = [format!("Press {} to start", button(Action::Fire)),
+                             format!("Difficulty: {} ({} / {} to change)",
+                                     self.difficulty.get_name().to_uppercase(),
+                                     button(Action::MoveLeft),
+                                     button(Action::MoveRight))];
+                renderer.draw_title("HIT AND RUN", &lines, c, gl, glyph_cache);
+                return;
             }
 
+            renderer.draw(&self.sim, alpha, c, gl, glyph_cache);
+            let toasts: Vec<&str> = self.toasts.iter().map(|t| t.0.as_str()).collect();
+            renderer.draw_toasts(&toasts, c, gl, glyph_cache);
+            if self.debug {
+                let height = self.sim.get_dimensions()[1];
+                renderer.draw_debug(&self.debug_lines(), height, c, gl, glyph_cache);
+            }
+            match self.state {
+                State::Paused => {
+                    let lines = [format!("{} to resume", button(Action::Pause))];
+                    renderer.draw_screen("PAUSED", &lines, c, gl, glyph_cache);
+                }
+                State::LevelTransition { level, .. } => {
+                    let title = format!("LEVEL {}", level);
+                    renderer.draw_screen(&title, &[], c, gl, glyph_cache);
+                }
+                State::GameOver { score, level } => {
+                    let mut lines = vec![format!("Score: {} | Level: {} | Difficulty: {}",
+                                                 score,
+                                                 level,
+                                                 self.difficulty.get_name())];
+                    lines.extend(self.stats_lines());
+                    lines.push(String::new());
+                    match self.name {
+                        Some(ref name) => {
+                            lines.push("New high score! Type your name and press Return"
+                                .to_string());
+                            lines.push(format!("Name: {}_", name));
+                        }
+                        None => {
+                            lines.extend(self.highscore_lines());
+                            lines.push(format!("{} to play again", button(Action::Reset)));
+                        }
+                    }
+                    renderer.draw_screen("GAME OVER", &lines, c, gl, glyph_cache);
+                }
+                State::Title | State::Playing => {}
+            }
         });
-
     }
 
-    /// Adds enemies to the game. Enemies spawn randomly in the map
-    fn add_enemy(&mut self) {
-        // get random x and y locations
-        let x = rand::thread_rng().gen_range(0.0, self.dimensions[0]);
-        let y = rand::thread_rng().gen_range(0.0, self.dimensions[1]);
+    /// Returns a line of text for every run in the high-score table. The
+    /// last run that made it in is marked
+    fn highscore_lines(&self) -> Vec<String> {
+        self.highscores
+            .get_entries()
+            .iter()
+            .enumerate()
+            .map(|(i, e)| {
+                let mark = if self.place == Some(i) { ">" } else { " " };
+                format!("{}{:>2}. {}   {}   level {}   {}   {}   {}",
+                        mark,
+                        i + 1,
+                        e.name,
+                        e.score,
+                        e.level,
+                        e.difficulty.get_name(),
+                        format_time(e.duration),
+                        e.date)
+            })
+            .collect()
+    }
 
-        // 1 / 5 chance of being able to move forward
-        let random_num = rand::thread_rng().gen_range(1, 5);
-        if random_num == 1 {
-            self.enemies.push(Enemy::new(x, y, true));
+    /// Returns lines of text for the debug overlay
+    fn debug_lines(&self) -> Vec<String> {
+        let sim = &self.sim;
+        let config = &sim.get_config().director;
+        let director = sim.get_director();
+        let state = if config.enabled {
+            format!("Director: rating {:+.2} | reinforcements {} | cooldowns x{:.2} | \
+                     bosses {:+}",
+                    director.get_rating(),
+                    sim.get_reinforcements(),
+                    director.get_cooldown_scale(config),
+                    (director.get_rating() * f64::from(config.boss_range)).round())
         } else {
-            self.enemies.push(Enemy::new(x, y, false));
+            "Director: off".to_string()
+        };
+        vec![format!("Tick: {} | Seed: {} | Enemies: {} | Bosses: {} | Bullets: {}",
+                     sim.get_tick(),
+                     sim.get_seed(),
+                     sim.get_enemies().len(),
+                     sim.get_bosses().len(),
+                     sim.get_player_bullets().len() + sim.get_enemy_bullets().len()),
+             state]
+    }
+
+    /// Returns lines of text that sum up how the run went
+    fn stats_lines(&self) -> Vec<String> {
+        let stats = self.sim.get_stats();
+        let levels: Vec<String> = stats.level_times.iter().map(|&t| format_time(t)).collect();
+        let mut lines = vec![format!("Accuracy: {:.0}% ({} of {} shots hit)",
+                     stats.get_accuracy() * 100.0,
+                     stats.hits,
+                     stats.shots),
+             format!("Killed: {} enemies, {} bosses | Health gained: {}",
+                     stats.enemies_killed,
+                     stats.bosses_killed,
+                     stats.health_gained),
+             format!("Damage taken: {} from bullets, {} from orbs",
+                     stats.bullet_damage,
+                     stats.orb_damage),
+             format!("Time: {} | In burst cooldown: {}",
+                     format_time(stats.get_time()),
+                     format_time(stats.burst_cooldown_time)),
+             format!("Time per level: {}", levels.join(" "))];
+        if stats.director_adjustments > 0 {
+            lines.push(format!("Director: {} adjustments, rating from {:+.2} to {:+.2}, \
+                                ended at {:+.2}",
+                               stats.director_adjustments,
+                               stats.lowest_rating,
+                               stats.highest_rating,
+                               self.sim.get_director().get_rating()));
         }
+        lines
     }
 
-    /// Adds boss to the game.
-    fn add_boss(&mut self) {
-        // get random x and y locations
-        let x = rand::thread_rng().gen_range(0.0, self.dimensions[0]);
-        let y = rand::thread_rng().gen_range(0.0, self.dimensions[1]);
+    /// Adds typed text to the name of a new high score
+    fn on_text(&mut self, text: &str) {
+        if let Some(ref mut name) = self.name {
+            name.extend(text.chars().filter(|c| !c.is_control()));
+            *name = name.chars().take(NAMELENGTH).collect();
+        }
+    }
+
+    /// Edits the name of a new high score. Return enters it in the table
+    fn on_name_key(&mut self, key: Key) {
+        match key {
+            Key::Backspace => {
+                if let Some(ref mut name) = self.name {
+                    name.pop();
+                }
+            }
+            Key::Return => {
+                if let Some(name) = self.name.take() {
+                    self.enter_highscore(&name);
+                }
+            }
+            _ => {}
+        }
+    }
 
-        self.bosses.push(Boss::new(x, y));
+    /// Puts the run that just ended in the high-score table and saves it
+    fn enter_highscore(&mut self, name: &str) {
+        let name = if name.trim().is_empty() { "???" } else { name };
+        let entry = Entry::new(name,
+                               self.sim.get_score(),
+                               self.sim.get_level(),
+                               self.sim.get_run_time(),
+                               self.sim.get_seed(),
+                               self.difficulty);
+        self.place = self.highscores.insert(entry);
+
+        if let Some(ref path) = self.highscores_path {
+            if let Err(e) = self.highscores.save(path) {
+                eprintln!("could not save high scores: {}", e);
+            }
+        }
     }
 
-    /// Updates the size of the window when it is resized
+    /// Updates the size of the game when the window is resized
     fn on_resize(&mut self, new_dimensions: &[u32; 2]) {
-        self.dimensions[0] = new_dimensions[0] as f64;
-        self.dimensions[1] = new_dimensions[1] as f64;
+        self.input.push(Input::Resize(new_dimensions[0] as f64, new_dimensions[1] as f64));
     }
 
-    /// Updates player desired location when mouse is moved
+    /// Updates player desired location, or where it aims when it is moved
+    /// with the keys, when mouse is moved
     fn on_mouse_mov(&mut self, motion: &[f64; 2]) {
-        self.player.desired_update(motion[0], motion[1]);
+        let input = match self.movement {
+            Movement::Mouse => Input::Target(motion[0], motion[1]),
+            Movement::Keyboard => Input::Aim(motion[0], motion[1]),
+        };
+        self.controller.window_input(input);
     }
 
-    /// Updates things in the game when a key is pressed
-    /// # Arguments
-    /// * `key` - the key that was pressed
-    ///
-    /// # Remarks
-    ///
-    /// Space is used to start shoot.
-    /// R is used to reset game
-    fn on_key_press(&mut self, key: Key) {
-        match key {
-            Key::Space => {
-                if !self.get_game_over() {
-                    self.player.start_shooting();
+    /// Starts moving the way of a movement button. They only move the player
+    /// when it is moved with the keys
+    fn hold(&mut self, button: Button) {
+        if self.movement == Movement::Keyboard && !self.held.contains(&button) {
+            self.held.push(button);
+            self.on_move();
+        }
+    }
+
+    /// Moves the player the way of the movement buttons that are held.
+    /// Diagonals are as fast as straight moves
+    fn on_move(&mut self) {
+        let mut actions: Vec<Action> = Vec::new();
+        for &button in &self.held {
+            if let Some(action) = self.bindings.get_action(button) {
+                if !actions.contains(&action) {
+                    actions.push(action);
                 }
             }
-            Key::R => {
-                self.reset();
+        }
+
+        let (mut x, mut y) = (0.0, 0.0);
+        for (dx, dy) in actions.iter().filter_map(Action::get_direction) {
+            x += dx;
+            y += dy;
+        }
+        let length = f64::hypot(x, y);
+        if length > 0.0 {
+            x /= length;
+            y /= length;
+        }
+        self.controller.window_input(Input::Move(x, y));
+    }
+
+    /// Does the action bound to a key or mouse button when it is pressed
+    /// # Arguments
+    /// * `button` - the button that was pressed
+    fn on_press(&mut self, button: Button) {
+        // keys type the name of a new high score instead of doing their action
+        if self.name.is_some() {
+            if let Button::Keyboard(key) = button {
+                self.on_name_key(key);
+            }
+            return;
+        }
+
+        match self.bindings.get_action(button) {
+            // fire starts the game on the title screen
+            Some(Action::Fire) if self.state == State::Title => {
+                self.state = State::transition(self.sim.get_level());
             }
+            // left and right pick the difficulty on the title screen
+            Some(Action::MoveLeft) if self.state == State::Title => {
+                let difficulty = self.difficulty.previous();
+                self.set_difficulty(difficulty);
+            }
+            Some(Action::MoveRight) if self.state == State::Title => {
+                let difficulty = self.difficulty.next();
+                self.set_difficulty(difficulty);
+            }
+            Some(Action::Fire) => self.controller.window_input(Input::Shoot(true)),
+            Some(Action::Dash) => self.controller.window_input(Input::Dash),
+            // the reset is applied by the next step, so it has to run
+            Some(Action::Reset) if self.state != State::Title => {
+                self.input.push(Input::Reset);
+                self.state = State::Playing;
+            }
+            Some(Action::Pause) => self.state = self.state.toggle_pause(),
+            Some(Action::Quicksave) => self.quicksave(),
+            Some(Action::Quickload) => self.quickload(),
+            Some(Action::Debug) => self.debug = !self.debug,
+            Some(action) if action.get_direction().is_some() => self.hold(button),
             _ => {}
         }
     }
 
-    /// Resets the game
-    fn reset(&mut self) {
-        self.enemies.clear();
-        self.player_bullets.clear();
-        self.enemy_bullets.clear();
-        self.bosses.clear();
-        self.score = 0;
-        self.level = 1;
-        self.current_kills = 0;
-        self.player.reset(self.dimensions[0], self.dimensions[1]);
-        self.add_enemy();
-        self.update_game_over(false);
+    /// Saves the whole game to the quicksave file
+    fn quicksave(&self) {
+        match snapshot::save(&self.sim, QUICKSAVE) {
+            Ok(()) => println!("game saved to {}", QUICKSAVE),
+            Err(e) => eprintln!("could not save game: {}", e),
+        }
     }
 
-    /// Called when a key is released
+    /// Replaces the game with the one in the quicksave file
+    fn quickload(&mut self) {
+        // a replay can't reproduce a game that was loaded halfway through
+        if self.recorder.is_some() {
+            eprintln!("quickload is disabled while recording");
+            return;
+        }
+
+        match snapshot::load::<Simulation, _>(QUICKSAVE) {
+            Ok(mut sim) => {
+                // keep playing with the config and script we were started
+                // with, on the difficulty the game was saved on
+                self.difficulty = sim.get_difficulty();
+                sim.set_config(self.difficulty.apply(&self.base_config));
+                sim.set_script(self.sim.get_script().clone());
+                self.state = if sim.get_game_over() {
+                    State::GameOver {
+                        score: sim.get_score(),
+                        level: sim.get_level(),
+                    }
+                } else {
+                    State::Playing
+                };
+                self.sim = sim;
+                self.achievements.on_load();
+                self.name = None;
+                self.input.clear();
+                self.accumulator = 0.0;
+            }
+            Err(e) => eprintln!("could not load game: {}", e),
+        }
+    }
+
+    /// Called when a key or mouse button is released
     /// # Arguments
-    /// *  `key` - the key that is released
+    /// *  `button` - the button that is released
     /// # Remarks
-    /// When spacebar is released the player stops shooting
-    fn on_key_release(&mut self, key: Key) {
-        match key {
-            Key::Space => {
-                self.player.stop_shooting();
-            }
-            _ => {}
+    /// When a fire button is released the player stops shooting
+    fn on_release(&mut self, button: Button) {
+        if let Some(Action::Fire) = self.bindings.get_action(button) {
+            self.controller.window_input(Input::Shoot(false));
+        }
+
+        // stop moving that way once a movement button is let go
+        if self.held.contains(&button) {
+            self.held.retain(|&b| b != button);
+            self.on_move();
         }
     }
 
@@ -345,94 +694,95 @@ impl Game {
     /// * `gl`     - Graphics used to draw on window
     /// * `glyph_cache - Used for font
     pub fn run(&mut self,
-               mut window: &mut Window,
-               mut gl: &mut GlGraphics,
-               mut glyph_cache: &mut GlyphCache) {
+               window: &mut Window,
+               gl: &mut GlGraphics,
+               glyph_cache: &mut GlyphCache) {
+
+        // textures can only be made once the window has loaded opengl
+        let renderer = Renderer::new();
 
         // check for events and set the fps
         let mut events = Events::new(EventSettings::new());
         events.set_ups(FPS);
 
-        // add an enemy to start off with
-        self.add_enemy();
-
         while let Some(e) = events.next(window) {
-
-            // make sure the game isnt over
-            if !self.get_game_over() {
+            // replays don't listen to the window
+            if !self.replaying {
                 // check mouse location
                 if let Some(m) = e.mouse_cursor_args() {
                     self.on_mouse_mov(&m);
                 }
 
-                // on key release
-                if let Some(k) = e.release_args() {
-                    match k {
-                        Button::Keyboard(key) => self.on_key_release(key),
-                        _ => {}
-                    }
+                // on key or mouse button press
+                if let Some(button) = e.press_args() {
+                    self.on_press(button);
                 }
 
-                // upon resize we change dimensions of game
-                if let Some(r) = e.resize_args() {
-                    self.on_resize(&r);
+                // typed text
+                if let Some(text) = e.text_args() {
+                    self.on_text(&text);
                 }
 
-                // on update
-                if let Some(r) = e.update_args() {
-                    self.on_update(&r);
+                // on key or mouse button release
+                if let Some(button) = e.release_args() {
+                    self.on_release(button);
                 }
-            };
 
-            // on key press
-            if let Some(k) = e.press_args() {
-                match k {
-                    Button::Keyboard(key) => self.on_key_press(key),
-                    _ => {}
+                // upon resize we change dimensions of game
+                if let Some(r) = e.resize_args() {
+                    self.on_resize(&r);
                 }
             }
 
+            // on update
+            if let Some(u) = e.update_args() {
+                self.on_update(&u);
+            }
+
             // upon render we draw things
-            if let Some(u) = e.render_args() {
-                self.on_draw(&u, &mut gl, &mut glyph_cache);
+            if let Some(r) = e.render_args() {
+                self.on_draw(&r, &renderer, gl, glyph_cache);
             }
         }
 
+        // the replay is written as the game goes, it only has to be finished
+        if let Some(ref mut recorder) = self.recorder {
+            match recorder.finish() {
+                Ok(()) => println!("replay saved to {}", recorder.get_path().display()),
+                Err(e) => eprintln!("could not save replay: {}", e),
+            }
+        }
     }
+}
 
-    /// Returns the current score
-    fn get_score(&self) -> u32 {
-        self.score
-    }
-
-    /// Returns the current level
-    fn get_level(&self) -> u32 {
-        self.level
-    }
-
-    /// Returns the amount of kills in the current level
-    fn get_kills(&self) -> u32 {
-        self.current_kills
-    }
-
-    /// Returns whether we have moved to new level
-    fn new_level(&self) -> bool {
-        self.current_kills == self.level
-    }
-
-    /// Update the level and reset current_kills
-    fn update_level(&mut self) {
-        self.level += 1;
-        self.current_kills = 0;
-    }
-
-    /// Sets the value of game over
-    fn update_game_over(&mut self, b: bool) {
-        self.game_over = b;
-    }
+/// Returns seconds as minutes and seconds, like `2:05`
+fn format_time(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
 
-    /// Returns whether the game is over
-    fn get_game_over(&mut self) -> bool {
-        self.game_over
+/// Reads a file kept in the data directory. Unreadable lines are skipped and
+/// the file they were in is backed up. Returns `None` if the file can't be
+/// read at all, so it isn't overwritten
+fn load_kept<T, F>(path: &Path, what: &str, load: F) -> Option<T>
+    where F: FnOnce(&Path) -> io::Result<(T, usize)>
+{
+    match load(path) {
+        Ok((value, 0)) => Some(value),
+        Ok((value, skipped)) => {
+            let backup = path.with_extension("bak");
+            eprintln!("skipped {} unreadable lines of {}, backed up to {}",
+                      skipped,
+                      path.display(),
+                      backup.display());
+            if let Err(e) = fs::copy(path, &backup) {
+                eprintln!("could not back up {}: {}", what, e);
+            }
+            Some(value)
+        }
+        Err(e) => {
+            eprintln!("could not read {}, they won't be saved: {}", what, e);
+            None
+        }
     }
 }
@@ -0,0 +1,225 @@
+//! The best runs, kept between games in the user's data directory.
+//!
+//! The file starts with a header holding its version, followed by a line for
+//! every run: score, level, duration, seed, date, difficulty and name, in
+//! that order. Lines that can't be read are skipped, so a damaged file loses
+//! only the runs on the damaged lines. Files of version 1 have no difficulty;
+//! their runs were played on normal.
+
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+
+use config::difficulty::Difficulty;
+use constants::game_constants::{HIGHSCORES, MAXSCORES, NAMELENGTH};
+use data;
+
+/// Version of the file format. Files of later versions are not read
+pub const VERSION: u32 = 2;
+
+/// Start of the first line of every high-score file
+const HEADER: &str = "hit_n_run highscores";
+
+/// A run in the high-score table
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    /// Name the player entered
+    pub name: String,
+    pub score: u32,
+    /// Level the run reached
+    pub level: u32,
+    /// Seconds the run lasted
+    pub duration: f64,
+    /// Seed the run started with, so it can be played again
+    pub seed: u64,
+    /// Day the run ended, like `2017-05-04`
+    pub date: String,
+    /// Difficulty the run was played on
+    pub difficulty: Difficulty,
+}
+
+impl Entry {
+    /// Returns an entry for a run that ended today. The name is cut short
+    /// and has its whitespace tidied so it fits on one line of the file
+    pub fn new(name: &str,
+               score: u32,
+               level: u32,
+               duration: f64,
+               seed: u64,
+               difficulty: Difficulty)
+               -> Self {
+        Entry {
+            name: clean_name(name),
+            score,
+            level,
+            duration,
+            seed,
+            date: Local::now().format("%Y-%m-%d").to_string(),
+            difficulty,
+        }
+    }
+
+    /// Reads an entry from a line of a file of `version`
+    fn parse(line: &str, version: u32) -> Option<Self> {
+        // version 1 has no difficulty
+        let fields = if version == 1 { 6 } else { 7 };
+        let mut words: Vec<&str> = line.splitn(fields, ' ').collect();
+        if words.len() != fields {
+            return None;
+        }
+        let difficulty = if version == 1 {
+            Difficulty::Normal
+        } else {
+            Difficulty::parse(words.remove(5))?
+        };
+
+        let duration: f64 = words[2].parse().ok()?;
+        if !duration.is_finite() || duration < 0.0 {
+            return None;
+        }
+        let name = clean_name(words[5]);
+        if name.is_empty() {
+            return None;
+        }
+
+        Some(Entry {
+            score: words[0].parse().ok()?,
+            level: words[1].parse().ok()?,
+            duration,
+            seed: words[3].parse().ok()?,
+            date: words[4].to_string(),
+            difficulty,
+            name,
+        })
+    }
+}
+
+/// Returns a name without control characters or runs of whitespace, at most
+/// `NAMELENGTH` characters long
+pub fn clean_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(NAMELENGTH)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// The best runs, best first
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HighScores {
+    entries: Vec<Entry>,
+}
+
+impl HighScores {
+    /// Returns an empty table
+    pub fn new() -> Self {
+        HighScores { entries: Vec::new() }
+    }
+
+    /// Returns where the table is kept, in the data directory of the user.
+    /// `None` when the system doesn't have one
+    pub fn default_path() -> Option<PathBuf> {
+        data::get_path(HIGHSCORES)
+    }
+
+    /// Reads a table. A missing file gives an empty table. Also returns how
+    /// many lines were skipped because they couldn't be read
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<(Self, usize)> {
+        let mut bytes = Vec::new();
+        match File::open(path) {
+            Ok(mut file) => file.read_to_end(&mut bytes)?,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok((HighScores::new(), 0))
+            }
+            Err(e) => return Err(e),
+        };
+        // a few bad bytes shouldn't cost the whole table
+        Ok(HighScores::parse(&String::from_utf8_lossy(&bytes)))
+    }
+
+    /// Reads a table from text. Also returns how many lines were skipped
+    /// because they couldn't be read. A file without the header of a known
+    /// version has every line skipped
+    pub fn parse(text: &str) -> (Self, usize) {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let mut scores = HighScores::new();
+
+        let version = match lines.next() {
+            Some(line) => {
+                let words: Vec<&str> = line.split_whitespace().collect();
+                match words.split_last() {
+                    Some((version, header)) if header.join(" ") == HEADER => {
+                        version.parse().ok().filter(|v| (1..=VERSION).contains(v))
+                    }
+                    _ => None,
+                }
+            }
+            None => return (scores, 0),
+        };
+        let version = match version {
+            Some(version) => version,
+            None => return (scores, 1 + lines.count()),
+        };
+
+        let mut skipped = 0;
+        for line in lines {
+            match Entry::parse(line.trim(), version) {
+                Some(entry) => {
+                    scores.insert(entry);
+                }
+                None => skipped += 1,
+            }
+        }
+        (scores, skipped)
+    }
+
+    /// Writes the table. The old file is only replaced once the new one is
+    /// written in full, so a crash can't leave half a table behind
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = format!("{} {}\n", HEADER, VERSION);
+        for e in &self.entries {
+            out.push_str(&format!("{} {} {} {} {} {} {}\n",
+                                  e.score,
+                                  e.level,
+                                  e.duration,
+                                  e.seed,
+                                  e.date,
+                                  e.difficulty.get_name(),
+                                  e.name));
+        }
+        data::replace_file(path.as_ref(), &out)
+    }
+
+    /// Returns whether a run with `score` would make it into the table
+    pub fn qualifies(&self, score: u32) -> bool {
+        score > 0 &&
+        (self.entries.len() < MAXSCORES || self.entries.iter().any(|e| e.score < score))
+    }
+
+    /// Adds a run to the table. Runs tied with older ones are placed below
+    /// them. Returns the place of the run, or `None` if it didn't make it
+    pub fn insert(&mut self, entry: Entry) -> Option<usize> {
+        let place = self.entries
+            .iter()
+            .position(|e| e.score < entry.score)
+            .unwrap_or(self.entries.len());
+        if place >= MAXSCORES {
+            return None;
+        }
+        self.entries.insert(place, entry);
+        self.entries.truncate(MAXSCORES);
+        Some(place)
+    }
+
+    /// Returns the runs, best first
+    pub fn get_entries(&self) -> &[Entry] {
+        &self.entries
+    }
+}
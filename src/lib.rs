@@ -4,10 +4,30 @@ extern crate glutin_window;
 extern crate opengl_graphics;
 extern crate rand;
 extern crate find_folder;
+extern crate dirs;
+extern crate chrono;
 
 // need this for documentation
 pub mod vector;
+pub mod snapshot;
+pub mod pool;
+pub mod rng;
 pub mod constants;
+pub mod config;
+pub mod settings;
 pub mod weapons;
+pub mod collision;
+pub mod events;
 pub mod models;
+pub mod script;
+pub mod stats;
+pub mod director;
+pub mod simulation;
+pub mod controller;
+pub mod render;
+pub mod replay;
+pub mod data;
+pub mod highscores;
+pub mod achievements;
+pub mod state;
 pub mod game;
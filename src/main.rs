@@ -1,35 +1,232 @@
 extern crate piston;
-extern crate graphics;
 extern crate glutin_window;
 extern crate opengl_graphics;
 extern crate rand;
+extern crate hit_n_run;
 
 use opengl_graphics::glyph_cache::GlyphCache;
 use piston::window::WindowSettings;
 use glutin_window::GlutinWindow as Window;
 use opengl_graphics::{GlGraphics, OpenGL};
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process;
 
-mod game;
-mod models;
-mod constants;
-mod vector;
-mod weapons;
+use hit_n_run::achievements::Achievements;
+use hit_n_run::config::Config;
+use hit_n_run::constants::game_constants::{CONFIG, SETTINGS};
+use hit_n_run::config::difficulty::Difficulty;
+use hit_n_run::controller::bot::{Bot, Strategy};
+use hit_n_run::events::Logger;
+use hit_n_run::game;
+use hit_n_run::highscores::HighScores;
+use hit_n_run::replay::Replay;
+use hit_n_run::script::Script;
+use hit_n_run::settings::Settings;
 
 // use width and height for game
-use constants::sizes;
+use hit_n_run::constants::sizes;
+
+/// Options passed on the command line
+struct Options {
+    /// Seed for the game. Random when not given
+    seed: Option<u64>,
+    /// File to record the input to
+    record: Option<PathBuf>,
+    /// Replay file to play back
+    replay: Option<PathBuf>,
+    /// Config file with gameplay values
+    config: Option<PathBuf>,
+    /// Script with the levels of the game
+    levels: Option<PathBuf>,
+    /// Settings file with the key and mouse bindings
+    settings: Option<PathBuf>,
+    /// Whether every game event is printed
+    log_events: bool,
+    /// Whether the built in bot plays instead of the mouse
+    bot: bool,
+    /// Difficulty to start on. Picked on the title screen when not given
+    difficulty: Option<Difficulty>,
+}
+
+/// Parses the command line arguments
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        seed: None,
+        record: None,
+        replay: None,
+        config: None,
+        levels: None,
+        settings: None,
+        log_events: false,
+        bot: false,
+        difficulty: None,
+    };
+    let mut args = env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let value = args.next().ok_or("--seed needs a value")?;
+                let seed = value.parse().map_err(|_| format!("invalid seed: {}", value))?;
+                options.seed = Some(seed);
+            }
+            "--record" => {
+                options.record = Some(PathBuf::from(args.next().ok_or("--record needs a path")?));
+            }
+            "--replay" => {
+                options.replay = Some(PathBuf::from(args.next().ok_or("--replay needs a path")?));
+            }
+            "--config" => {
+                options.config = Some(PathBuf::from(args.next().ok_or("--config needs a path")?));
+            }
+            "--levels" => {
+                options.levels = Some(PathBuf::from(args.next().ok_or("--levels needs a path")?));
+            }
+            "--settings" => {
+                let path = args.next().ok_or("--settings needs a path")?;
+                options.settings = Some(PathBuf::from(path));
+            }
+            "--log-events" => options.log_events = true,
+            "--bot" => options.bot = true,
+            "--difficulty" => {
+                let value = args.next().ok_or("--difficulty needs a value")?;
+                let difficulty = Difficulty::parse(&value)
+                    .ok_or_else(|| format!("invalid difficulty: {}", value))?;
+                options.difficulty = Some(difficulty);
+            }
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+
+    if options.bot && options.replay.is_some() {
+        return Err("--bot can't be used with --replay".to_string());
+    }
+    // a replay is played on the difficulty it was recorded on
+    if options.difficulty.is_some() && options.replay.is_some() {
+        return Err("--difficulty can't be used with --replay".to_string());
+    }
+    Ok(options)
+}
 
 fn main() {
+    let options = parse_args().unwrap_or_else(|e| {
+        eprintln!("{}\nusage: main [--seed <n>] [--record <file>] [--replay <file>] \
+                   [--config <file>] [--levels <file>] [--settings <file>] \
+                   [--log-events] [--bot] [--difficulty <easy|normal|hard|nightmare>]",
+                  e);
+        process::exit(1);
+    });
+
+    // the default config file is optional, one given on the command line isn't
+    let config_path = match options.config {
+        Some(path) => Some(path),
+        None if Path::new(CONFIG).exists() => Some(PathBuf::from(CONFIG)),
+        None => None,
+    };
+    let config = match config_path {
+        Some(ref path) => Config::load(path),
+        None => Ok(Config::default()),
+    };
+    let config = config.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+
+    // the default levels are built in
+    let script = match options.levels {
+        Some(ref path) => Script::load(path),
+        None => Ok(Script::default()),
+    };
+    let script = script.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+
+    // like the config, the default settings file is optional
+    let settings = match options.settings {
+        Some(ref path) => Settings::load(path),
+        None if Path::new(SETTINGS).exists() => Settings::load(SETTINGS),
+        None => Ok(Settings::default()),
+    };
+    let settings = settings.unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+
+    // create the game, either from a replay or from a seed
+    let mut g = match options.replay {
+        Some(ref path) => {
+            let replay = Replay::load(path).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                process::exit(1);
+            });
+            let mismatches = replay.get_mismatches(&config, &script);
+            if !mismatches.is_empty() {
+                eprintln!("warning: the replay was recorded with a different {}, it won't play \
+                           out the same",
+                          mismatches.join(" and "));
+            }
+            game::Game::from_replay(replay, config, script)
+        }
+        None => {
+            // pick a seed and print it so a game can be reproduced
+            let seed = options.seed.unwrap_or_else(rand::random);
+            println!("seed: {}", seed);
+            game::Game::new(sizes::INITWIDTH as f64,
+                            sizes::INITHEIGHT as f64,
+                            seed,
+                            config,
+                            script)
+        }
+    };
+
+    g.set_settings(settings);
+
+    if let Some(difficulty) = options.difficulty {
+        g.set_difficulty(difficulty);
+    }
+
+    // replays and bots don't make it into the high-score table or unlock
+    // achievements
+    if options.replay.is_none() && !options.bot {
+        match (HighScores::default_path(), Achievements::default_path()) {
+            (Some(highscores), Some(achievements)) => {
+                g.keep_highscores(highscores);
+                g.keep_achievements(achievements);
+            }
+            _ => eprintln!("no data directory found, high scores and achievements won't be \
+                            kept"),
+        }
+    }
+
+    if let Some(path) = options.record {
+        g.record(path);
+    }
+
+    if options.bot {
+        g.set_controller(Box::new(Bot::new(Strategy::Hunter)));
+    }
+
+    if options.log_events {
+        g.subscribe(Box::new(Logger));
+    }
+
+    // pick up changes to the config while the game runs
+    if let Some(path) = config_path {
+        g.watch_config(path);
+    }
+
     // create opengl and window
     let opengl = OpenGL::V3_2;
-    let mut window: Window = WindowSettings::new("HIT AND RUN",
-                                                 [sizes::INITWIDTH, sizes::INITHEIGHT])
+    let size = g.get_dimensions();
+    let mut window: Window = WindowSettings::new("HIT AND RUN", [size[0] as u32, size[1] as u32])
         .exit_on_esc(true)
         .build()
         .expect("Error unwrapping window");
 
-    // create gl graphics and game
+    // create gl graphics
     let mut gl = GlGraphics::new(opengl);
-    let mut g = game::Game::new(sizes::INITWIDTH as f64, sizes::INITHEIGHT as f64);
 
     // create glych cache for fonts
     let mut glyph_cache = GlyphCache::new("assets/Roboto-Regular.ttf").expect("Error unwraping fonts");
@@ -1,18 +1,15 @@
-extern crate piston;
-extern crate graphics;
-extern crate glutin_window;
-extern crate opengl_graphics;
 extern crate rand;
 
-use opengl_graphics::glyph_cache::GlyphCache;
 use rand::Rng;
-use opengl_graphics::GlGraphics;
-use piston::input::*;
 use std::f64;
 use vector::Vector;
-use constants::boss_constants::*;
+use collision;
+use collision::Collider;
+use collision::shape::Shape;
+use config::Config;
+use pool::Pool;
 use weapons::bullet::Bullet;
-use constants::color::*;
+use snapshot::{Reader, Snapshot, SnapshotError, Writer};
 
 pub struct Boss {
     /// Position of the boss
@@ -27,92 +24,79 @@ pub struct Boss {
 
 impl Boss {
     /// Returns a boss
-    pub fn new(x: f64, y: f64) -> Self {
+    pub fn new<R: Rng>(x: f64, y: f64, config: &Config, rng: &mut R) -> Self {
         Boss {
             pos: Vector::new(x, y),
-            health: STARTHEALTH,
-            cooldown: rand::thread_rng().gen_range(0.0, COOLDOWN),
-            rotation: rand::thread_rng().gen_range(0.0, 2.0 * f64::consts::PI),
+            health: config.boss.start_health,
+            cooldown: rng.gen_range(0.0, config.boss.cooldown),
+            rotation: rng.gen_range(0.0, 2.0 * f64::consts::PI),
         }
     }
 
-    /// Check if the boss was hit by a bullet
-    pub fn hit(&mut self, ref mut b: &mut Bullet) {
-        // check the distance between bullet and boss
-        let xdiff = self.pos.x - b.get_x();
-        let ydiff = self.pos.y - b.get_y();
-        let dist = (xdiff.powi(2) + ydiff.powi(2)).sqrt();
-
+    /// Check if the boss was hit by a bullet. Returns whether it was
+    pub fn hit(&mut self, b: &mut Bullet, config: &Config) -> bool {
         // update health and kill bullet if boss is hit
-        if self.get_alive() && dist < BOSSD / 2.0 - EPSILON {
-            self.decrease_health();
-            b.set_alive(false);
+        if !self.get_alive() || !collision::collide(self, b, config) {
+            return false;
         }
+        self.decrease_health();
+        b.set_alive(false);
+        true
     }
 
     /// Returns a Vector of Bullets
-    pub fn shoot_bullets(&mut self) -> Vec<Bullet> {
-        let mut bullets: Vec<Bullet> = Vec::new();
-        for x in 0..5 {
-            let rot = x as f64 * 2.0 * f64::consts::PI / TOTBULL+ self.rotation;
-            bullets.push(Bullet::new(self.pos.x, self.pos.y, rot, true));
+    pub fn shoot_bullets(&mut self, config: &Config, bullets: &mut Pool<Bullet>) {
+        for x in 0..config.boss.volley_size {
+            let rot = self.get_gun_rotation(x, config);
+            bullets.insert(Bullet::new(self.pos.x, self.pos.y, rot, true));
         }
-        bullets
     }
 
-    /// Update the boss position and velocity. Return a bullet if it is shooting.
-    pub fn update(&mut self, args: &UpdateArgs) -> Option<Vec<Bullet>> {
+    /// Returns the rotation of a gun. The guns are spread evenly around the boss
+    pub fn get_gun_rotation(&self, gun: u32, config: &Config) -> f64 {
+        gun as f64 * 2.0 * f64::consts::PI / config.boss.volley_size as f64 + self.rotation
+    }
+
+    /// Update the boss position and velocity. Bullets it shoots are added to
+    /// `bullets`.
+    pub fn update(&mut self, dt: f64, config: &Config, bullets: &mut Pool<Bullet>) {
 
         // check if boss can shoot
         if self.can_shoot() {
-            self.update_cooldown(args.dt);
-            return Some(self.shoot_bullets());
+            self.update_cooldown(dt, config);
+            self.shoot_bullets(config, bullets);
+            return;
         }
 
         // update cooldown
-        self.update_cooldown(args.dt);
+        self.update_cooldown(dt, config);
         // update rotation
-        self.update_rotation(args.dt);
-        None
+        self.update_rotation(dt, config);
     }
 
-    /// Draws the boss
-    pub fn draw(&self, c: graphics::Context, gl: &mut GlGraphics, glyph_cache: &mut GlyphCache) {
-        use graphics::*;
-
-        let circle = rectangle::square(0.0, 0.0, BOSSD);
-        let square = rectangle::square(0.0, 0.0, GUND);
-
-        // create transform matrix
-        let transform = c.transform
-            .trans(self.pos.x, self.pos.y)
-            .trans(-BOSSD / 2.0, -BOSSD / 2.0);
+    /// Returns whether the boss is alive
+    pub fn get_alive(&self) -> bool {
+        self.health != 0
+    }
 
-        // Draw the boss
-        ellipse(GREEN, circle, transform, gl);
+    /// Returns the x position of the boss
+    pub fn get_x(&self) -> f64 {
+        self.pos.x
+    }
 
-        // Draw where the bullets where go
-        for x in 0..(TOTBULL as i64) {
-            let rot = x as f64 * 2.0 * f64::consts::PI / TOTBULL + self.rotation;
-            let transform2 = c.transform
-                .trans(self.pos.x, self.pos.y)
-                .rot_rad(rot)
-                .trans(BOSSD / 2.0 - GUND / 2.0, -GUND / 2.0);
-            rectangle(PINK, square, transform2, gl);
-        }
+    /// Returns the y position of the boss
+    pub fn get_y(&self) -> f64 {
+        self.pos.y
+    }
 
-        // display the health
-        text(WHITE,
-             FONTSIZE,
-             format!("{}", self.get_health()).as_str(),
-             glyph_cache,
-             c.transform.trans(self.pos.x, self.pos.y),
-             gl);
+    /// Returns the position of the boss
+    pub fn get_pos(&self) -> Vector {
+        self.pos
     }
 
-    /// Returns whether the boss is alive
-    pub fn get_alive(&self) -> bool {
-        self.health != 0
+    /// Returns the rotation of the boss
+    pub fn get_rotation(&self) -> f64 {
+        self.rotation
     }
 
     /// Returns the health of the boss
@@ -125,23 +109,58 @@ impl Boss {
         self.health -= 1;
     }
 
+    /// Adopts a new config. Cooldowns longer than the new one are cut short
+    pub fn apply_config(&mut self, config: &Config) {
+        self.cooldown = self.cooldown.min(config.boss.cooldown);
+    }
+
     /// Updates the cooldown of shooting
-    fn update_cooldown(&mut self, dt: f64) {
+    fn update_cooldown(&mut self, dt: f64, config: &Config) {
         if self.cooldown < 0.0 {
-            self.cooldown = COOLDOWN;
+            self.cooldown = config.boss.cooldown;
         } else {
             self.cooldown -= dt;
         }
     }
 
     /// Updates the rotation of the boss
-    fn update_rotation(&mut self, dt: f64) {
+    fn update_rotation(&mut self, dt: f64, config: &Config) {
         // update rotation of the boss
-        self.rotation += dt * ROTMULT ;
+        self.rotation += dt * config.boss.rotation_speed;
     }
 
     /// Returns whether the boss can shoot
     fn can_shoot(&self) -> bool {
         self.cooldown < 0.0
     }
-}
\ No newline at end of file
+}
+
+/// The boss's circle is `epsilon` smaller than it is drawn
+impl Collider for Boss {
+    fn get_shape(&self, config: &Config) -> Shape {
+        Shape::Circle {
+            center: self.pos,
+            radius: config.boss.diameter / 2.0 - config.boss.epsilon,
+        }
+    }
+}
+
+impl Snapshot for Boss {
+    fn save(&self, w: &mut Writer) {
+        w.tag("boss");
+        self.pos.save(w);
+        w.value(self.health);
+        w.value(self.cooldown);
+        w.value(self.rotation);
+    }
+
+    fn load(r: &mut Reader) -> Result<Self, SnapshotError> {
+        r.tag("boss")?;
+        Ok(Boss {
+            pos: Vector::load(r)?,
+            health: r.value()?,
+            cooldown: r.value()?,
+            rotation: r.value()?,
+        })
+    }
+}
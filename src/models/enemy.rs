@@ -1,24 +1,22 @@
-extern crate piston;
-extern crate graphics;
-extern crate glutin_window;
-extern crate opengl_graphics;
 extern crate rand;
 
-use opengl_graphics::glyph_cache::GlyphCache;
 use rand::Rng;
-use opengl_graphics::GlGraphics;
-use piston::input::*;
 use std::f64;
 use vector::Vector;
-use constants::enemy_constants::*;
+use collision;
+use collision::Collider;
+use collision::shape::Shape;
+use config::Config;
 use weapons::bullet::Bullet;
 use weapons::orb::Orb;
-use constants::color::*;
+use snapshot::{Reader, Snapshot, SnapshotError, Writer};
 
 /// Enemy
 pub struct Enemy {
     /// Position of the enemy
     pos: Vector,
+    /// Position before the last update
+    prev_pos: Vector,
     /// Desired position of the enemy
     desired_pos: Vector,
     /// Velocity of the enemy
@@ -37,153 +35,105 @@ pub struct Enemy {
 
 impl Enemy {
     /// Returns an enemy
-    pub fn new(x: f64, y: f64, b: bool) -> Self {
+    pub fn new<R: Rng>(x: f64, y: f64, b: bool, config: &Config, rng: &mut R) -> Self {
         Enemy {
             pos: Vector::new(x, y),
+            prev_pos: Vector::new(x, y),
             desired_pos: Vector::new(0.0, 0.0),
             vel: Vector::new(0.0, 0.0),
             rotation: 0.0,
             forward: b,
-            health: STARTHEALTH,
-            cooldown: rand::thread_rng().gen_range(0.0, COOLDOWN),
-            orb: Orb::new(x, y, ORBITD),
+            health: config.enemy.start_health,
+            cooldown: rng.gen_range(0.0, config.enemy.cooldown),
+            orb: Orb::new(x, y, config.enemy.orbit_diameter, config, rng),
         }
     }
 
-    /// Check if enemy is hit by a bullet
-    pub fn hit(&mut self, ref mut b: &mut Bullet) {
-        // check the distance between bullet and enemy
-        let xdiff = self.pos.x - b.get_x();
-        let ydiff = self.pos.y - b.get_y();
-        let dist = (xdiff.powi(2) + ydiff.powi(2)).sqrt();
-
+    /// Check if enemy is hit by a bullet. Returns whether it was
+    pub fn hit(&mut self, b: &mut Bullet, config: &Config) -> bool {
         // update health and kill bullet if enemy is hit
-        if self.get_alive() && dist < ENEMYD / 2.0 - EPSILON {
-            self.decrease_health();
-            b.set_alive(false);
+        if !self.get_alive() || !collision::collide(self, b, config) {
+            return false;
         }
+        self.decrease_health();
+        b.set_alive(false);
+        true
     }
 
     /// Updates the desired postion and rotation.
     pub fn desired_update(&mut self, desx: f64, desy: f64) {
-        // update desired x position
-        self.desired_pos.x = desx;
-        self.desired_pos.y = desy;
-
-        // update angle
-        let xdiff = desx - self.pos.x;
-        let ydiff = desy - self.pos.y;
-        let mag = (xdiff.powi(2) + ydiff.powi(2)).sqrt();
-        let unitx = xdiff / mag;
-        let unity = ydiff / mag;
+        // update desired position
+        self.desired_pos = Vector::new(desx, desy);
 
-        // turn unit vector to radians
-        self.rotation = unity.atan2(unitx);
+        // face the desired position
+        self.rotation = (self.desired_pos - self.pos).angle();
     }
 
     /// Move the player to a new postion
-    fn mov(&mut self, width: f64, height: f64) {
+    fn mov(&mut self, width: f64, height: f64, moveback: f64) {
         // make sure player doesnt leave the screen
         // check x position
         if self.pos.x < 0.0 {
-            self.pos.x += MOVEBACK;
+            self.pos.x += moveback;
         } else if self.pos.x > width {
-            self.pos.x -= MOVEBACK;
+            self.pos.x -= moveback;
         }
 
         // check y position
         if self.pos.y < 0.0 {
-            self.pos.y += MOVEBACK;
+            self.pos.y += moveback;
         } else if self.pos.y > height {
-            self.pos.y -= MOVEBACK;
+            self.pos.y -= moveback;
         }
 
         // update x and y
-        self.pos.x += self.vel.x;
-        self.pos.y += self.vel.y;
+        self.pos += self.vel;
     }
 
     /// Update the enemys position and velocity. Return a bullet if it is shooting.
-    pub fn update(&mut self,
-                  args: &UpdateArgs,
-                  desx: f64,
-                  desy: f64,
-                  dimensions: &[f64; 2])
-                  -> Option<Bullet> {
+    pub fn update<R: Rng>(&mut self,
+                          dt: f64,
+                          desx: f64,
+                          desy: f64,
+                          dimensions: &[f64; 2],
+                          config: &Config,
+                          rng: &mut R)
+                          -> Option<Bullet> {
+        self.prev_pos = self.pos;
 
         // update desired postion and rotation
         self.desired_update(desx, desy);
         let dist = self.pos.dist(&self.desired_pos);
 
         // if the enemy is not at desired location keep moving
-        if dist > ENEMYD && self.forward {
-            self.vel.x = VEL * args.dt * self.rotation.cos();
-            self.vel.y = VEL * args.dt * self.rotation.sin();
+        if dist > config.enemy.diameter && self.forward {
+            self.vel = Vector::from_angle(self.rotation) * (config.enemy.velocity * dt);
         } else {
             self.vel.reset();
         }
 
         // move enemy
-        self.mov(dimensions[0], dimensions[1]);
+        self.mov(dimensions[0], dimensions[1], config.enemy.moveback);
 
         // update orb
-        self.orb.update(self.pos.x, self.pos.y, args.dt);
+        self.orb.update(self.pos, dt, config);
 
         // check if enemy can shoot
         if self.can_shoot() {
-            self.update_cooldown(args.dt);
+            self.update_cooldown(dt, config);
 
             // has small error when shooting
-            let rot = rand::thread_rng().gen_range(-SHOOTINGERR, SHOOTINGERR);
-            return Some(Bullet::new(self.pos.x + ENEMYD / 2.0 * self.rotation.cos(),
-                                    self.pos.y + ENEMYD / 2.0 * self.rotation.sin(),
-                                    self.rotation + rot,
-                                    true));
+            let err = config.enemy.shooting_error;
+            let rot = if err > 0.0 { rng.gen_range(-err, err) } else { 0.0 };
+            let gun = self.pos + Vector::from_angle(self.rotation) * (config.enemy.diameter / 2.0);
+            return Some(Bullet::new(gun.x, gun.y, self.rotation + rot, true));
         }
 
         // update cooldown
-        self.update_cooldown(args.dt);
+        self.update_cooldown(dt, config);
         None
     }
 
-    /// Draws the enemy
-    pub fn draw(&self, c: graphics::Context, gl: &mut GlGraphics, glyph_cache: &mut GlyphCache) {
-        use graphics::*;
-
-        let circle = rectangle::square(0.0, 0.0, ENEMYD);
-        let square = rectangle::square(0.0, 0.0, GUND);
-        let orb = rectangle::square(0.0, 0.0, ORBD);
-
-        // create transform matrix
-        let transform = c.transform
-            .trans(self.pos.x, self.pos.y)
-            .trans(-ENEMYD / 2.0, -ENEMYD / 2.0);
-
-        let transform2 = c.transform
-            .trans(self.pos.x, self.pos.y)
-            .rot_rad(self.rotation)
-            .trans(ENEMYD / 2.0 - GUND / 2.0, -GUND / 2.0);
-
-        let transform3 =
-            c.transform.trans(self.get_orb_x(), self.get_orb_y()).trans(-ORBD / 2.0, -ORBD / 2.0);
-
-        // get orb color
-        let color = if self.get_orb_active() { ORANGE } else { ANGEL };
-
-        // Draw a box rotating around the middle of the screen.
-        ellipse(BLUE, circle, transform, gl);
-        rectangle(PINK, square, transform2, gl);
-        ellipse(color, orb, transform3, gl);
-
-        // display the health
-        text(WHITE,
-             FONTSIZE,
-             format!("{}", self.get_health()).as_str(),
-             glyph_cache,
-             c.transform.trans(self.pos.x, self.pos.y),
-             gl);
-    }
-
     /// Returns whether the enemy is alive
     pub fn get_alive(&self) -> bool {
         self.health != 0
@@ -195,16 +145,16 @@ impl Enemy {
     }
 
     /// Updates the cooldown of shooting
-    fn update_cooldown(&mut self, dt: f64) {
+    fn update_cooldown(&mut self, dt: f64, config: &Config) {
         if self.cooldown < 0.0 {
-            self.cooldown = COOLDOWN;
+            self.cooldown = config.enemy.cooldown;
         } else {
             self.cooldown -= dt;
         }
     }
 
     /// Returns the health of the enemy
-    fn get_health(&self) -> u32 {
+    pub fn get_health(&self) -> u32 {
         self.health
     }
 
@@ -213,6 +163,31 @@ impl Enemy {
         self.health -= 1;
     }
 
+    /// Return the x position of the enemy
+    pub fn get_x(&self) -> f64 {
+        self.pos.x
+    }
+
+    /// Return the y position of the enemy
+    pub fn get_y(&self) -> f64 {
+        self.pos.y
+    }
+
+    /// Return the position of the enemy
+    pub fn get_pos(&self) -> Vector {
+        self.pos
+    }
+
+    /// Return the position of the enemy before the last update
+    pub fn get_prev_pos(&self) -> Vector {
+        self.prev_pos
+    }
+
+    /// Return the rotation of the enemy
+    pub fn get_rotation(&self) -> f64 {
+        self.rotation
+    }
+
     /// Return the x position of the orb
     pub fn get_orb_x(&self) -> f64 {
         self.orb.get_x()
@@ -223,13 +198,74 @@ impl Enemy {
         self.orb.get_y()
     }
 
+    /// Return the position of the orb
+    pub fn get_orb_pos(&self) -> Vector {
+        self.orb.get_pos()
+    }
+
+    /// Return the position of the orb before the last update
+    pub fn get_orb_prev_pos(&self) -> Vector {
+        self.orb.get_prev_pos()
+    }
+
     /// Return whether the orb is active or not
     pub fn get_orb_active(&self) -> bool {
         self.orb.get_active()
     }
 
+    /// Returns the orb
+    pub fn get_orb(&self) -> &Orb {
+        &self.orb
+    }
+
     /// Set whether the orb is active or not
     pub fn set_orb_active(&mut self, b: bool) {
         self.orb.set_active(b);
     }
+
+    /// Adopts a new config. Cooldowns longer than the new one are cut short
+    pub fn apply_config(&mut self, config: &Config) {
+        self.cooldown = self.cooldown.min(config.enemy.cooldown);
+        self.orb.apply_config(config.enemy.orbit_diameter, config);
+    }
+}
+
+/// The enemy's circle is `epsilon` smaller than it is drawn
+impl Collider for Enemy {
+    fn get_shape(&self, config: &Config) -> Shape {
+        Shape::Circle {
+            center: self.pos,
+            radius: config.enemy.diameter / 2.0 - config.enemy.epsilon,
+        }
+    }
+}
+
+impl Snapshot for Enemy {
+    fn save(&self, w: &mut Writer) {
+        w.tag("enemy");
+        self.pos.save(w);
+        self.prev_pos.save(w);
+        self.desired_pos.save(w);
+        self.vel.save(w);
+        w.value(self.rotation);
+        w.value(self.forward);
+        w.value(self.health);
+        w.value(self.cooldown);
+        self.orb.save(w);
+    }
+
+    fn load(r: &mut Reader) -> Result<Self, SnapshotError> {
+        r.tag("enemy")?;
+        Ok(Enemy {
+            pos: Vector::load(r)?,
+            prev_pos: Vector::load(r)?,
+            desired_pos: Vector::load(r)?,
+            vel: Vector::load(r)?,
+            rotation: r.value()?,
+            forward: r.value()?,
+            health: r.value()?,
+            cooldown: r.value()?,
+            orb: Orb::load(r)?,
+        })
+    }
 }
@@ -1,30 +1,36 @@
-extern crate piston;
-extern crate graphics;
-extern crate glutin_window;
-extern crate opengl_graphics;
-extern crate find_folder;
-
-use opengl_graphics::GlGraphics;
-use piston::input::*;
 use std::f64;
 use vector::Vector;
 use weapons::bullet::Bullet;
 use models::enemy::Enemy;
-use constants::player_constants::*;
-use constants::color::*;
-use opengl_graphics::Texture;
-use std::result::Result;
+use collision;
+use collision::Collider;
+use collision::shape::Shape;
+use config::Config;
+use snapshot::{Reader, Snapshot, SnapshotError, Writer};
 
 /// User controlled player
 pub struct Player {
     /// Position of the player
     pos: Vector,
+    /// Position before the last update
+    prev_pos: Vector,
     /// Velocity of the player
     vel: Vector,
     /// Position where the player wants to be
     desired_pos: Vector,
-    /// Rotation of the player
+    /// Whether the player moves the way of `direction` instead of towards
+    /// `desired_pos`
+    direct: bool,
+    /// Direction set by the movement keys. At most one long
+    direction: Vector,
+    /// Whether the gun points at `aim_pos` instead of the way the player moves
+    aiming: bool,
+    /// Position the player aims at
+    aim_pos: Vector,
+    /// Rotation of the player, the way it moves
     rotation: f64,
+    /// Rotation of the gun, the way the player shoots
+    aim: f64,
     /// Health of the player
     health: u32,
     /// Whether the player is shooting
@@ -34,190 +40,164 @@ pub struct Player {
     scooldown: f64,
     /// Cooldown between bursts
     bcooldown: f64,
-    /// Texture for image of player
-    texture: Result<Texture, String>,
+    /// Time left of the current dash
+    dash_time: f64,
+    /// Cooldown between dashes
+    dcooldown: f64,
 }
 
 impl Player {
     /// Returns a Player
-    pub fn new(xpos: f64, ypos: f64) -> Self {
+    pub fn new(xpos: f64, ypos: f64, config: &Config) -> Self {
         Player {
             pos: Vector::new(xpos, ypos),
+            prev_pos: Vector::new(xpos, ypos),
             vel: Vector::new(0.0, 0.0),
             desired_pos: Vector::new(xpos, ypos),
+            direct: false,
+            direction: Vector::new(0.0, 0.0),
+            aiming: false,
+            aim_pos: Vector::new(xpos, ypos),
             rotation: 0.0,
-            health: STARTHEALTH,
+            aim: 0.0,
+            health: config.player.start_health,
             is_shooting: false,
-            shots: STARTSHOTS,
+            shots: config.player.start_shots,
             scooldown: 0.0,
             bcooldown: 0.0,
-            texture: Texture::from_path(find_folder::Search::ParentsThenKids(3, 3)
-                .for_folder("assets")
-                .unwrap()
-                .join("player.png")),
+            dash_time: 0.0,
+            dcooldown: 0.0,
         }
     }
 
-    /// Check if the player collided with a plaer
-    pub fn collide(&mut self, ref mut enemy: &mut Enemy) {
-        let xdiff = self.pos.x - enemy.get_orb_x();
-        let ydiff = self.pos.y - enemy.get_orb_y();
-        let dist = (xdiff.powi(2) + ydiff.powi(2)).sqrt();
-
+    /// Check if the player collided with the orb of an enemy. Returns whether it did
+    pub fn collide(&mut self, enemy: &mut Enemy, config: &Config) -> bool {
         // check if we crashed.
-        if self.get_alive() && enemy.get_orb_active() && dist < PLAYERD / 2.0 {
-            self.decrease_health();
-            enemy.set_orb_active(false);
+        if !self.get_alive() || !enemy.get_orb_active() ||
+           !collision::collide(self, enemy.get_orb(), config) {
+            return false;
         }
+        self.decrease_health();
+        enemy.set_orb_active(false);
+        true
     }
-    /// Check if player is hit by a bullet
-    pub fn hit(&mut self, ref mut b: &mut Bullet) {
-        // check the distance between bullet and player
-        let xdiff = self.pos.x - b.get_x();
-        let ydiff = self.pos.y - b.get_y();
-        let dist = (xdiff.powi(2) + ydiff.powi(2)).sqrt();
-
+    /// Check if player is hit by a bullet. Returns whether it was
+    pub fn hit(&mut self, b: &mut Bullet, config: &Config) -> bool {
         // update health and kill bullet if player is hit
-        if self.get_alive() && dist < PLAYERD / 2.0 - EPSILON {
-            self.decrease_health();
-            b.set_alive(false);
+        if !self.get_alive() || !collision::collide(self, b, config) {
+            return false;
         }
+        self.decrease_health();
+        b.set_alive(false);
+        true
     }
 
     /// Updates the desired position and the rotation.
     pub fn desired_update(&mut self, mouse_x: f64, mouse_y: f64) {
-        // update desired x position
-        self.desired_pos.x = mouse_x;
-        self.desired_pos.y = mouse_y;
+        // update desired position
+        self.desired_pos = Vector::new(mouse_x, mouse_y);
+        self.direct = false;
+    }
+
+    /// Makes the player move the way of a direction instead of towards the
+    /// desired position. Directions longer than one are shortened to one
+    pub fn direction_update(&mut self, x: f64, y: f64) {
+        let direction = Vector::new(x, y);
+        self.direction = if direction.length() > 1.0 {
+            direction.normalize()
+        } else {
+            direction
+        };
+        self.direct = true;
+    }
+
+    /// Makes the gun point at a position from now on, whichever way the
+    /// player moves
+    pub fn aim_update(&mut self, x: f64, y: f64) {
+        self.aim_pos = Vector::new(x, y);
+        self.aiming = true;
     }
 
     /// Move the player based on its velocity
-    fn mov(&mut self, width: f64, height: f64) {
+    fn mov(&mut self, width: f64, height: f64, moveback: f64) {
         // make sure player doesnt leave the screen
         // check x position
         if self.pos.x < 0.0 {
-            self.pos.x += MOVEBACK;
+            self.pos.x += moveback;
         } else if self.pos.x > width {
-            self.pos.x -= MOVEBACK;
+            self.pos.x -= moveback;
         }
 
         // check y position
         if self.pos.y < 0.0 {
-            self.pos.y += MOVEBACK;
+            self.pos.y += moveback;
         } else if self.pos.y > height {
-            self.pos.y -= MOVEBACK;
+            self.pos.y -= moveback;
         }
 
         // update x and y
-        self.pos.x += self.vel.x;
-        self.pos.y += self.vel.y;
+        self.pos += self.vel;
     }
 
     fn update_angle(&mut self) {
-        // update angle
-        let xdiff = self.desired_pos.x - self.pos.x;
-        let ydiff = self.desired_pos.y - self.pos.y;
-        let mag = (xdiff.powi(2) + ydiff.powi(2)).sqrt();
-        let unitx = xdiff / mag;
-        let unity = ydiff / mag;
-
-        // turn unit vector to radians
-        self.rotation = unity.atan2(unitx);
+        // face the desired position
+        self.rotation = (self.desired_pos - self.pos).angle();
     }
 
     /// Update the players position and velocity. Return a bullet if it is shooting.
-    pub fn update(&mut self, args: &UpdateArgs, dimensions: &[f64; 2]) -> Option<Bullet> {
+    pub fn update(&mut self, dt: f64, dimensions: &[f64; 2], config: &Config) -> Option<Bullet> {
+        self.prev_pos = self.pos;
+
         // get distance to desired location
         let dist = self.pos.dist(&self.desired_pos);
 
-        // if the player is not at desired location keep velocity else stop velocity
-        if dist > DDISTANCE {
+        // move the way of the keys, else if the player is not at desired location
+        // keep velocity else stop velocity
+        if self.direct {
+            if self.direction != Vector::default() {
+                self.rotation = self.direction.angle();
+            }
+            self.vel = self.direction * (config.player.velocity * dt);
+        } else if dist > config.player.stop_distance {
             self.update_angle();
-            self.vel.x = VEL * args.dt * self.rotation.cos();
-            self.vel.y = VEL * args.dt * self.rotation.sin();
+            self.vel = Vector::from_angle(self.rotation) * (config.player.velocity * dt);
         } else {
             self.vel.reset();
         }
 
+        // dashes keep going the way the player moves, even past where it wants to be
+        if self.dash_time > 0.0 {
+            self.dash_time -= dt;
+            self.vel = Vector::from_angle(self.rotation) *
+                       (config.player.velocity * config.player.dash_speed * dt);
+        }
+        self.dcooldown = (self.dcooldown - dt).max(0.0);
+
         // move player and reset velocity
-        self.mov(dimensions[0], dimensions[1]);
+        self.mov(dimensions[0], dimensions[1], config.player.moveback);
+
+        // point the gun at the aim, or the way the player moves without one
+        self.aim = if self.aiming {
+            (self.aim_pos - self.pos).angle()
+        } else {
+            self.rotation
+        };
 
         // update cooldown
-        self.update_cooldown(args.dt);
+        self.update_cooldown(dt, config);
 
         // return bullet
         if self.can_shoot() && self.get_shooting() {
-            self.scooldown = SHOTCOOLDOWN;
+            let gun = self.pos + Vector::from_angle(self.aim) * (config.player.diameter / 2.0);
+            self.scooldown = config.player.shot_cooldown;
             self.shots -= 1;
-            return Some(Bullet::new(self.pos.x + PLAYERD / 2.0 * self.rotation.cos(),
-                                    self.pos.y + PLAYERD / 2.0 * self.rotation.sin(),
-                                    self.rotation,
-                                    false));
+            return Some(Bullet::new(gun.x, gun.y, self.aim, false));
         }
 
         // no bullet shot
         None
     }
 
-    /// Draws the player
-    pub fn draw(&self, c: graphics::Context, gl: &mut GlGraphics) {
-        use graphics::*;
-
-        // circle fot the body
-        let circle = rectangle::square(0.0, 0.0, PLAYERD);
-        // square for the gun
-        let square = rectangle::square(0.0, 0.0, GUND);
-
-        // create transform matrix for body and gun
-        let bodytrans = c.transform
-            .trans(self.pos.x, self.pos.y)
-            .rot_rad(self.rotation)
-            .trans(-PLAYERD / 2.0, -PLAYERD / 2.0);
-
-        let guntrans = c.transform
-            .trans(self.pos.x, self.pos.y)
-            .rot_rad(self.rotation)
-            .trans(PLAYERD / 2.0 - GUND / 2.0, -GUND / 2.0);
-
-        // create transfrom matrix for cooldown bar
-        let bartrans = c.transform.trans(self.pos.x - PLAYERD / 2.0, self.pos.y);
-
-        // draw a circle rotating around the middle of the screen.
-        ellipse(PINK, circle, bodytrans, gl);
-        rectangle(WHITE, square, guntrans, gl);
-
-        // check if we have an image for the player
-        match self.texture {
-            Ok(ref t) => image(t, bodytrans, gl),
-            _ => {}
-        }
-
-        if self.bcooldown <= 0.0 {
-            // get length of bars and draw current shots bar and cooldown bar
-            let slength = PLAYERD / (STARTSHOTS as f64) * (self.shots as f64);
-            let clength = PLAYERD / (SHOTCOOLDOWN as f64) * (self.scooldown as f64);
-
-            line(ORANGE,
-                 BARWIDTH,
-                 [0.0, SBARDIST, slength, SBARDIST],
-                 bartrans,
-                 gl);
-            line(ANGEL,
-                 BARWIDTH,
-                 [0.0, CBARDIST, clength, CBARDIST],
-                 bartrans,
-                 gl);
-        } else {
-            // get length for bar and draw cooldown bar
-            let blength = PLAYERD / (BURSTCOOLDOWN as f64) * (self.bcooldown);
-            line(ANGEL,
-                 BARWIDTH,
-                 [0.0, BBARDIST, blength, BBARDIST],
-                 bartrans,
-                 gl);
-        }
-    }
-
     /// Return the health
     pub fn get_health(&self) -> u32 {
         self.health
@@ -239,16 +219,41 @@ impl Player {
     }
 
     /// Reset the player's position and health.
-    pub fn reset(&mut self, width: f64, height: f64) {
-        self.pos.x = width / 2.0;
-        self.pos.y = height / 2.0;
-        self.health = STARTHEALTH;
+    pub fn reset(&mut self, width: f64, height: f64, config: &Config) {
+        self.pos = Vector::new(width / 2.0, height / 2.0);
+        self.prev_pos = self.pos;
+        self.health = config.player.start_health;
         self.rotation = 0.0;
+        self.aim = 0.0;
         self.bcooldown = 0.0;
         self.scooldown = 0.0;
-        self.desired_pos.reset();
+        // like a new player, it stays put until it is told where to go
+        self.desired_pos = self.pos;
         self.vel.reset();
-        self.shots = STARTSHOTS;
+        self.shots = config.player.start_shots;
+        self.dash_time = 0.0;
+        self.dcooldown = 0.0;
+    }
+
+    /// Adopts a new config. Cooldowns and bursts longer than the new ones are
+    /// cut short
+    pub fn apply_config(&mut self, config: &Config) {
+        self.scooldown = self.scooldown.min(config.player.shot_cooldown);
+        self.bcooldown = self.bcooldown.min(config.player.burst_cooldown);
+        self.shots = self.shots.min(config.player.start_shots);
+        self.dash_time = self.dash_time.min(config.player.dash_time);
+        self.dcooldown = self.dcooldown.min(config.player.dash_cooldown);
+    }
+
+    /// Starts a dash unless the last one is still cooling down. Returns
+    /// whether it did
+    pub fn dash(&mut self, config: &Config) -> bool {
+        if !self.get_alive() || self.dcooldown > 0.0 {
+            return false;
+        }
+        self.dash_time = config.player.dash_time;
+        self.dcooldown = config.player.dash_cooldown;
+        true
     }
 
     /// Start shooting
@@ -262,12 +267,12 @@ impl Player {
     }
 
     /// Update the cooldown of shooting
-    fn update_cooldown(&mut self, dt: f64) {
+    fn update_cooldown(&mut self, dt: f64, config: &Config) {
         // we ran out of burst shots. so now we update burst cooldown
         if self.shots == 0 {
             self.scooldown = 0.0;
-            self.bcooldown = BURSTCOOLDOWN;
-            self.shots = STARTSHOTS;
+            self.bcooldown = config.player.burst_cooldown;
+            self.shots = config.player.start_shots;
         }
 
         // check if we can shoot
@@ -302,4 +307,100 @@ impl Player {
     pub fn get_y(&self) -> f64 {
         self.pos.y
     }
+
+    /// Return the position of the player
+    pub fn get_pos(&self) -> Vector {
+        self.pos
+    }
+
+    /// Return the position of the player before the last update
+    pub fn get_prev_pos(&self) -> Vector {
+        self.prev_pos
+    }
+
+    /// Return the rotation of the player
+    pub fn get_rotation(&self) -> f64 {
+        self.rotation
+    }
+
+    /// Return the rotation of the gun
+    pub fn get_aim(&self) -> f64 {
+        self.aim
+    }
+
+    /// Return the amount of shots left in the current burst
+    pub fn get_shots(&self) -> u32 {
+        self.shots
+    }
+
+    /// Return the cooldown between shots
+    pub fn get_shot_cooldown(&self) -> f64 {
+        self.scooldown
+    }
+
+    /// Return the cooldown between bursts
+    pub fn get_burst_cooldown(&self) -> f64 {
+        self.bcooldown
+    }
+
+    /// Return the cooldown between dashes
+    pub fn get_dash_cooldown(&self) -> f64 {
+        self.dcooldown
+    }
+}
+
+/// The player's circle is `epsilon` smaller than it is drawn
+impl Collider for Player {
+    fn get_shape(&self, config: &Config) -> Shape {
+        Shape::Circle {
+            center: self.pos,
+            radius: config.player.diameter / 2.0 - config.player.epsilon,
+        }
+    }
+}
+
+impl Snapshot for Player {
+    fn save(&self, w: &mut Writer) {
+        w.tag("player");
+        self.pos.save(w);
+        self.prev_pos.save(w);
+        self.vel.save(w);
+        self.desired_pos.save(w);
+        w.value(self.direct);
+        self.direction.save(w);
+        w.value(self.aiming);
+        self.aim_pos.save(w);
+        w.value(self.rotation);
+        w.value(self.aim);
+        w.value(self.health);
+        w.value(self.is_shooting);
+        w.value(self.shots);
+        w.value(self.scooldown);
+        w.value(self.bcooldown);
+        w.value(self.dash_time);
+        w.value(self.dcooldown);
+    }
+
+    fn load(r: &mut Reader) -> Result<Self, SnapshotError> {
+        r.tag("player")?;
+        Ok(Player {
+            pos: Vector::load(r)?,
+            prev_pos: Vector::load(r)?,
+            vel: Vector::load(r)?,
+            desired_pos: Vector::load(r)?,
+            direct: r.value()?,
+            direction: Vector::load(r)?,
+            aiming: r.value()?,
+            aim_pos: Vector::load(r)?,
+            rotation: r.value()?,
+            aim: r.value()?,
+            health: r.value()?,
+            is_shooting: r.value()?,
+            shots: r.value()?,
+            scooldown: r.value()?,
+            bcooldown: r.value()?,
+            dash_time: r.value()?,
+            dcooldown: r.value()?,
+        })
+    }
 }
@@ -0,0 +1,472 @@
+use std::ops::{Index, IndexMut};
+use std::slice;
+
+use snapshot::{Reader, Snapshot, SnapshotError, Writer};
+
+/// Refers to a value in a `Pool`. Once the value is removed the handle goes
+/// stale, even if its slot is used again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Handle {
+    /// Slot the value is in
+    index: u32,
+    /// Generation of the slot when the value was put in it
+    generation: u32,
+}
+
+impl Handle {
+    /// Returns the slot the value is in
+    pub fn get_index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+/// A slot of a pool
+struct Slot<T> {
+    /// Bumped every time the slot is emptied
+    generation: u32,
+    /// Value in the slot
+    value: Option<T>,
+}
+
+/// Storage that reuses the slots of removed values, so filling it up again
+/// doesn't allocate. Values stay in the same slot until they are removed.
+pub struct Pool<T> {
+    /// Every slot, used or not
+    slots: Vec<Slot<T>>,
+    /// Empty slots. The last one is used first
+    free: Vec<u32>,
+    /// Amount of values in the pool
+    len: usize,
+}
+
+impl<T> Pool<T> {
+    /// Returns an empty pool
+    pub fn new() -> Self {
+        Pool {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Adds a value and returns its handle
+    pub fn insert(&mut self, value: T) -> Handle {
+        self.len += 1;
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.value = Some(value);
+                Handle {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    value: Some(value),
+                });
+                Handle {
+                    index: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        }
+    }
+
+    /// Removes a value. Returns None if the handle is stale
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        if !self.contains(handle) {
+            return None;
+        }
+        Some(self.take(handle.index))
+    }
+
+    /// Empties a used slot
+    fn take(&mut self, index: u32) -> T {
+        let slot = &mut self.slots[index as usize];
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(index);
+        self.len -= 1;
+        slot.value.take().expect("slot is empty")
+    }
+
+    /// Returns whether the handle's value is still in the pool
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    /// Returns the value of a handle. Returns None if it is stale
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        match self.slots.get(handle.index as usize) {
+            Some(slot) if slot.generation == handle.generation => slot.value.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a handle. Returns None if it is stale
+    pub fn get_mut(&mut self, handle: Handle) -> Option<&mut T> {
+        match self.slots.get_mut(handle.index as usize) {
+            Some(slot) if slot.generation == handle.generation => slot.value.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Returns the handle of the value in a slot
+    pub fn handle(&self, index: usize) -> Option<Handle> {
+        match self.slots.get(index) {
+            Some(slot) if slot.value.is_some() => {
+                Some(Handle {
+                    index: index as u32,
+                    generation: slot.generation,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the amount of values in the pool
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns whether the pool has no values
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the values in slot order
+    pub fn iter<'a>(&'a self) -> Iter<'a, T> {
+        Iter { slots: self.slots.iter() }
+    }
+
+    /// Returns the values in slot order
+    pub fn iter_mut<'a>(&'a mut self) -> IterMut<'a, T> {
+        IterMut { slots: self.slots.iter_mut() }
+    }
+
+    /// Returns the values and their handles in slot order
+    pub fn handles(&self) -> impl Iterator<Item = (Handle, &T)> {
+        self.slots.iter().enumerate().filter_map(|(i, s)| {
+            s.value.as_ref().map(|v| {
+                (Handle {
+                    index: i as u32,
+                    generation: s.generation,
+                },
+                 v)
+            })
+        })
+    }
+
+    /// Removes every value `keep` returns false for, in slot order
+    pub fn retain<F: FnMut(&mut T) -> bool>(&mut self, mut keep: F) {
+        for index in 0..self.slots.len() {
+            let remove = match self.slots[index].value {
+                Some(ref mut value) => !keep(value),
+                None => false,
+            };
+            if remove {
+                self.take(index as u32);
+            }
+        }
+    }
+
+    /// Removes every value. Slots are kept and reused from the first one
+    pub fn clear(&mut self) {
+        for index in 0..self.slots.len() {
+            if self.slots[index].value.is_some() {
+                self.take(index as u32);
+            }
+        }
+        self.free.sort_unstable_by(|a, b| b.cmp(a));
+    }
+}
+
+impl<T> Default for Pool<T> {
+    fn default() -> Self {
+        Pool::new()
+    }
+}
+
+/// Iterates over the values of a pool
+pub struct Iter<'a, T: 'a> {
+    slots: slice::Iter<'a, Slot<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.slots.by_ref().filter_map(|s| s.value.as_ref()).next()
+    }
+}
+
+/// Iterates over the values of a pool
+pub struct IterMut<'a, T: 'a> {
+    slots: slice::IterMut<'a, Slot<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.slots.by_ref().filter_map(|s| s.value.as_mut()).next()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Pool<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Pool<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+/// Panics if the handle is stale
+impl<T> Index<Handle> for Pool<T> {
+    type Output = T;
+
+    fn index(&self, handle: Handle) -> &T {
+        self.get(handle).expect("stale handle")
+    }
+}
+
+/// Panics if the handle is stale
+impl<T> IndexMut<Handle> for Pool<T> {
+    fn index_mut(&mut self, handle: Handle) -> &mut T {
+        self.get_mut(handle).expect("stale handle")
+    }
+}
+
+/// Saves the slots as well as the values so a loaded pool hands out the same
+/// slots and iterates in the same order
+impl<T: Snapshot> Snapshot for Pool<T> {
+    fn save(&self, w: &mut Writer) {
+        w.value(self.slots.len());
+        w.value(self.free.len());
+        for index in &self.free {
+            w.value(index);
+        }
+        for slot in &self.slots {
+            w.tag("slot");
+            w.value(slot.generation);
+            w.value(slot.value.is_some());
+            if let Some(ref value) = slot.value {
+                value.save(w);
+            }
+        }
+    }
+
+    fn load(r: &mut Reader) -> Result<Self, SnapshotError> {
+        let slot_count: usize = r.value()?;
+        let free_count: usize = r.value()?;
+        // the counts come from the file, so they can't be trusted to allocate
+        let mut free = Vec::new();
+        for _ in 0..free_count {
+            free.push(r.value()?);
+        }
+
+        let mut slots = Vec::new();
+        for _ in 0..slot_count {
+            r.tag("slot")?;
+            let generation = r.value()?;
+            let used: bool = r.value()?;
+            let value = if used { Some(T::load(r)?) } else { None };
+            slots.push(Slot { generation, value });
+        }
+
+        // every empty slot has to be free exactly once
+        let len = slots.iter().filter(|s| s.value.is_some()).count();
+        let mut seen = vec![false; slot_count];
+        for &index in &free {
+            let index = index as usize;
+            if index >= slot_count || seen[index] || slots[index].value.is_some() {
+                return Err(SnapshotError::Malformed(format!("invalid free slot: {}", index)));
+            }
+            seen[index] = true;
+        }
+        if free.len() + len != slot_count {
+            return Err(SnapshotError::Malformed("pool is missing free slots".to_string()));
+        }
+
+        Ok(Pool { slots, free, len })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Value that can be saved
+    #[derive(Debug, PartialEq)]
+    struct Number(u32);
+
+    impl Snapshot for Number {
+        fn save(&self, w: &mut Writer) {
+            w.tag("number");
+            w.value(self.0);
+        }
+
+        fn load(r: &mut Reader) -> Result<Self, SnapshotError> {
+            r.tag("number")?;
+            Ok(Number(r.value()?))
+        }
+    }
+
+    /// Returns a pool holding `values` in that order
+    fn pool(values: &[u32]) -> (Pool<Number>, Vec<Handle>) {
+        let mut pool = Pool::new();
+        let handles = values.iter().map(|&v| pool.insert(Number(v))).collect();
+        (pool, handles)
+    }
+
+    /// Returns the values of a pool in slot order
+    fn values(pool: &Pool<Number>) -> Vec<u32> {
+        pool.iter().map(|n| n.0).collect()
+    }
+
+    fn save(pool: &Pool<Number>) -> String {
+        let mut w = Writer::new();
+        pool.save(&mut w);
+        w.finish()
+    }
+
+    fn load_error(text: &str) -> String {
+        match Pool::<Number>::load(&mut Reader::new(text)) {
+            Err(SnapshotError::Malformed(message)) => message,
+            Err(e) => panic!("unexpected error: {:?}", e),
+            Ok(_) => panic!("pool should not load"),
+        }
+    }
+
+    #[test]
+    fn stale_handles() {
+        let (mut pool, handles) = pool(&[1, 2, 3]);
+        assert_eq!(pool.remove(handles[1]), Some(Number(2)));
+        assert!(!pool.contains(handles[1]));
+        assert_eq!(pool.get(handles[1]), None);
+        assert_eq!(pool.remove(handles[1]), None);
+        assert_eq!(pool.len(), 2);
+
+        // the slot is used again, but the old handle doesn't see the value
+        let new = pool.insert(Number(4));
+        assert_eq!(new.get_index(), handles[1].get_index());
+        assert_ne!(new, handles[1]);
+        assert_eq!(pool.get(handles[1]), None);
+        assert_eq!(pool.get_mut(handles[1]), None);
+        assert_eq!(pool.remove(handles[1]), None);
+        assert_eq!(pool[new], Number(4));
+        assert_eq!(values(&pool), vec![1, 4, 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "stale handle")]
+    fn indexing_with_a_stale_handle_panics() {
+        let (mut pool, handles) = pool(&[1]);
+        pool.remove(handles[0]);
+        pool.insert(Number(2));
+        let _ = &pool[handles[0]];
+    }
+
+    #[test]
+    fn generation_is_bumped_every_time_a_slot_is_emptied() {
+        let (mut pool, mut handles) = pool(&[1]);
+        let mut seen = handles.clone();
+        for i in 0..5 {
+            pool.remove(handles[0]);
+            handles[0] = pool.insert(Number(i));
+            assert!(!seen.contains(&handles[0]));
+            seen.push(handles[0]);
+        }
+        assert_eq!(pool.handle(0), Some(handles[0]));
+        assert_eq!(pool.len(), 1);
+
+        // generations wrap instead of overflowing
+        pool.slots[0].generation = u32::MAX;
+        let old = pool.handle(0).unwrap();
+        pool.remove(old);
+        let new = pool.insert(Number(9));
+        assert_eq!(pool.slots[0].generation, 0);
+        assert_ne!(old, new);
+    }
+
+    #[test]
+    fn retain_removes_in_slot_order() {
+        let (mut pool, handles) = pool(&[1, 2, 3, 4, 5]);
+        let mut visited = Vec::new();
+        pool.retain(|n| {
+            visited.push(n.0);
+            n.0 % 2 == 1
+        });
+        assert_eq!(visited, vec![1, 2, 3, 4, 5]);
+        assert_eq!(values(&pool), vec![1, 3, 5]);
+        assert_eq!(pool.len(), 3);
+        assert!(!pool.contains(handles[1]) && !pool.contains(handles[3]));
+        assert!(pool.contains(handles[4]));
+
+        // the last slot emptied is used first
+        assert_eq!(pool.insert(Number(6)).get_index(), 3);
+        assert_eq!(pool.insert(Number(7)).get_index(), 1);
+        assert_eq!(pool.insert(Number(8)).get_index(), 5);
+    }
+
+    #[test]
+    fn clear_reuses_slots_from_the_first() {
+        let (mut pool, handles) = pool(&[1, 2, 3, 4]);
+        pool.remove(handles[2]);
+        pool.remove(handles[0]);
+        pool.clear();
+        assert!(pool.is_empty());
+        assert_eq!(values(&pool), Vec::<u32>::new());
+        assert!(handles.iter().all(|&h| !pool.contains(h)));
+
+        let indices: Vec<usize> = (0..5).map(|i| pool.insert(Number(i)).get_index()).collect();
+        assert_eq!(indices, vec![0, 1, 2, 3, 4]);
+        assert_eq!(values(&pool), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn loaded_pool_hands_out_the_same_slots() {
+        let (mut pool, handles) = pool(&[1, 2, 3, 4]);
+        pool.remove(handles[1]);
+        pool.remove(handles[3]);
+        let text = save(&pool);
+        let mut loaded = Pool::<Number>::load(&mut Reader::new(&text)).unwrap();
+        assert_eq!(save(&loaded), text);
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[handles[2]], Number(3));
+        assert!(!loaded.contains(handles[1]));
+        assert_eq!(loaded.insert(Number(5)), pool.insert(Number(5)));
+        assert_eq!(loaded.insert(Number(6)), pool.insert(Number(6)));
+        assert_eq!(loaded.insert(Number(7)), pool.insert(Number(7)));
+    }
+
+    #[test]
+    fn load_refuses_a_bad_free_list() {
+        let slots = "slot 0 true number 1 slot 1 false slot 2 false";
+        assert!(Pool::<Number>::load(&mut Reader::new(&format!("3 2 1 2 {}", slots))).is_ok());
+
+        // out of range, twice, a used slot and one left out
+        assert_eq!(load_error(&format!("3 2 1 3 {}", slots)), "invalid free slot: 3");
+        assert_eq!(load_error(&format!("3 2 1 1 {}", slots)), "invalid free slot: 1");
+        assert_eq!(load_error(&format!("3 2 0 2 {}", slots)), "invalid free slot: 0");
+        assert_eq!(load_error(&format!("3 1 2 {}", slots)), "pool is missing free slots");
+    }
+
+    #[test]
+    fn load_doesnt_trust_counts() {
+        let huge = usize::MAX;
+        assert_eq!(load_error(&format!("1 {} 0", huge)), "unexpected end of file");
+        assert_eq!(load_error(&format!("{} 0 slot 0 false", huge)), "unexpected end of file");
+    }
+}
@@ -0,0 +1,363 @@
+extern crate graphics;
+extern crate opengl_graphics;
+extern crate find_folder;
+
+use opengl_graphics::GlGraphics;
+use opengl_graphics::Texture;
+use opengl_graphics::glyph_cache::GlyphCache;
+use std::result::Result;
+
+use config::Config;
+use constants::color::*;
+use constants::game_constants;
+use models::enemy::Enemy;
+use models::player::Player;
+use models::boss::Boss;
+use simulation::Simulation;
+use weapons::bullet::Bullet;
+
+/// Draws a simulation with piston's graphics
+pub struct Renderer {
+    /// Texture for image of player
+    player_texture: Result<Texture, String>,
+}
+
+impl Renderer {
+    /// Returns a renderer. Loads the textures from the assets folder
+    pub fn new() -> Self {
+        Renderer {
+            player_texture: Texture::from_path(find_folder::Search::ParentsThenKids(3, 3)
+                .for_folder("assets")
+                .unwrap()
+                .join("player.png")),
+        }
+    }
+
+    /// Draws the gameboard
+    ///
+    /// # Arguments
+    ///
+    /// * `alpha` - How far the simulation is into the next step. Positions
+    ///   are drawn this far between their last two steps
+    pub fn draw(&self,
+                sim: &Simulation,
+                alpha: f64,
+                c: graphics::Context,
+                gl: &mut GlGraphics,
+                glyph_cache: &mut GlyphCache) {
+        use graphics::*;
+
+        let config = sim.get_config();
+
+        // clear the screen
+        clear(BLACK, gl);
+
+        // draw enemy bullets
+        for bullet in sim.get_enemy_bullets() {
+            draw_bullet(bullet, alpha, config, c, gl);
+        }
+
+        // draw friendly bullets
+        for bullet in sim.get_player_bullets() {
+            draw_bullet(bullet, alpha, config, c, gl);
+        }
+
+        // draw enemies
+        for enemy in sim.get_enemies() {
+            draw_enemy(enemy, alpha, config, c, gl, glyph_cache);
+        }
+
+        // draw bosses
+        for boss in sim.get_bosses() {
+            draw_boss(boss, config, c, gl, glyph_cache);
+        }
+
+        // draw player
+        self.draw_player(sim.get_player(), alpha, config, c, gl);
+
+        // display score and health
+        text(WHITE,
+             game_constants::FONTSIZE,
+             format!("Score: {} | Health: {} | Level: {} | Level Kills: {}/{}",
+                     sim.get_score(),
+                     sim.get_player().get_health(),
+                     sim.get_level(),
+                     sim.get_kills(),
+                     sim.get_kill_target())
+                 .as_str(),
+             glyph_cache,
+             c.transform.trans(15.0, 15.0),
+             gl);
+
+    }
+
+    /// Draws messages below the HUD line, like unlocked achievements
+    pub fn draw_toasts(&self,
+                       toasts: &[&str],
+                       c: graphics::Context,
+                       gl: &mut GlGraphics,
+                       glyph_cache: &mut GlyphCache) {
+        use graphics::*;
+
+        for (i, toast) in toasts.iter().enumerate() {
+            let y = 35.0 + 20.0 * i as f64;
+            text(GREEN,
+                 game_constants::FONTSIZE,
+                 toast,
+                 glyph_cache,
+                 c.transform.trans(15.0, y),
+                 gl);
+        }
+    }
+
+    /// Draws debug information in the bottom left corner of a screen
+    /// `height` pixels high, the last line at the bottom
+    pub fn draw_debug(&self,
+                      lines: &[String],
+                      height: f64,
+                      c: graphics::Context,
+                      gl: &mut GlGraphics,
+                      glyph_cache: &mut GlyphCache) {
+        use graphics::*;
+
+        for (i, line) in lines.iter().rev().enumerate() {
+            let y = height - 15.0 - 20.0 * i as f64;
+            text(LIGHTBLUE,
+                 game_constants::FONTSIZE,
+                 line,
+                 glyph_cache,
+                 c.transform.trans(15.0, y),
+                 gl);
+        }
+    }
+
+    /// Draws a screen of its own, without the game behind it
+    pub fn draw_title(&self,
+                      title: &str,
+                      lines: &[String],
+                      c: graphics::Context,
+                      gl: &mut GlGraphics,
+                      glyph_cache: &mut GlyphCache) {
+        graphics::clear(BLACK, gl);
+        self.draw_screen(title, lines, c, gl, glyph_cache);
+    }
+
+    /// Draws a title and lines of text below it across the top of the screen
+    pub fn draw_screen(&self,
+                       title: &str,
+                       lines: &[String],
+                       c: graphics::Context,
+                       gl: &mut GlGraphics,
+                       glyph_cache: &mut GlyphCache) {
+        use graphics::*;
+
+        text(ORANGE,
+             game_constants::TITLESIZE,
+             title,
+             glyph_cache,
+             c.transform.trans(15.0, 75.0),
+             gl);
+
+        for (i, line) in lines.iter().enumerate() {
+            let y = 110.0 + 20.0 * i as f64;
+            text(WHITE,
+                 game_constants::FONTSIZE,
+                 line,
+                 glyph_cache,
+                 c.transform.trans(15.0, y),
+                 gl);
+        }
+    }
+
+    /// Draws the player
+    fn draw_player(&self,
+                   player: &Player,
+                   alpha: f64,
+                   config: &Config,
+                   c: graphics::Context,
+                   gl: &mut GlGraphics) {
+        use graphics::*;
+        use constants::player_constants::*;
+
+        let pos = player.get_prev_pos().lerp(&player.get_pos(), alpha);
+        let diameter = config.player.diameter;
+
+        // circle fot the body
+        let circle = rectangle::square(0.0, 0.0, diameter);
+        // square for the gun
+        let square = rectangle::square(0.0, 0.0, GUND);
+
+        // create transform matrix for body and gun
+        let bodytrans = c.transform
+            .trans(pos.x, pos.y)
+            .rot_rad(player.get_rotation())
+            .trans(-diameter / 2.0, -diameter / 2.0);
+
+        // the gun points where the player aims, not where it moves
+        let guntrans = c.transform
+            .trans(pos.x, pos.y)
+            .rot_rad(player.get_aim())
+            .trans(diameter / 2.0 - GUND / 2.0, -GUND / 2.0);
+
+        // create transfrom matrix for cooldown bar
+        let bartrans = c.transform.trans(pos.x - diameter / 2.0, pos.y);
+
+        // draw a circle rotating around the middle of the screen.
+        ellipse(PINK, circle, bodytrans, gl);
+        rectangle(WHITE, square, guntrans, gl);
+
+        // check if we have an image for the player
+        if let Ok(ref t) = self.player_texture {
+            image(t, bodytrans, gl);
+        }
+
+        if player.get_burst_cooldown() <= 0.0 {
+            // get length of bars and draw current shots bar and cooldown bar
+            let slength = diameter / (config.player.start_shots as f64) *
+                          (player.get_shots() as f64);
+            let clength = diameter / config.player.shot_cooldown * player.get_shot_cooldown();
+
+            line(ORANGE,
+                 BARWIDTH,
+                 [0.0, SBARDIST, slength, SBARDIST],
+                 bartrans,
+                 gl);
+            line(ANGEL,
+                 BARWIDTH,
+                 [0.0, CBARDIST, clength, CBARDIST],
+                 bartrans,
+                 gl);
+        } else {
+            // get length for bar and draw cooldown bar
+            let blength = diameter / config.player.burst_cooldown * player.get_burst_cooldown();
+            line(ANGEL,
+                 BARWIDTH,
+                 [0.0, BBARDIST, blength, BBARDIST],
+                 bartrans,
+                 gl);
+        }
+    }
+}
+
+impl Default for Renderer {
+    fn default() -> Self {
+        Renderer::new()
+    }
+}
+
+/// Draws an enemy and its orb
+fn draw_enemy(enemy: &Enemy,
+              alpha: f64,
+              config: &Config,
+              c: graphics::Context,
+              gl: &mut GlGraphics,
+              glyph_cache: &mut GlyphCache) {
+    use graphics::*;
+    use constants::enemy_constants::*;
+
+    let pos = enemy.get_prev_pos().lerp(&enemy.get_pos(), alpha);
+    let orb_pos = enemy.get_orb_prev_pos().lerp(&enemy.get_orb_pos(), alpha);
+    let diameter = config.enemy.diameter;
+    let orb_diameter = config.enemy.orb_diameter;
+
+    let circle = rectangle::square(0.0, 0.0, diameter);
+    let square = rectangle::square(0.0, 0.0, GUND);
+    let orb = rectangle::square(0.0, 0.0, orb_diameter);
+
+    // create transform matrix
+    let transform = c.transform
+        .trans(pos.x, pos.y)
+        .trans(-diameter / 2.0, -diameter / 2.0);
+
+    let transform2 = c.transform
+        .trans(pos.x, pos.y)
+        .rot_rad(enemy.get_rotation())
+        .trans(diameter / 2.0 - GUND / 2.0, -GUND / 2.0);
+
+    let transform3 = c.transform
+        .trans(orb_pos.x, orb_pos.y)
+        .trans(-orb_diameter / 2.0, -orb_diameter / 2.0);
+
+    // get orb color
+    let color = if enemy.get_orb_active() { ORANGE } else { ANGEL };
+
+    // Draw a box rotating around the middle of the screen.
+    ellipse(BLUE, circle, transform, gl);
+    rectangle(PINK, square, transform2, gl);
+    ellipse(color, orb, transform3, gl);
+
+    // display the health
+    text(WHITE,
+         FONTSIZE,
+         format!("{}", enemy.get_health()).as_str(),
+         glyph_cache,
+         c.transform.trans(pos.x, pos.y),
+         gl);
+}
+
+/// Draws a boss and the guns its volley will come out of
+fn draw_boss(boss: &Boss,
+             config: &Config,
+             c: graphics::Context,
+             gl: &mut GlGraphics,
+             glyph_cache: &mut GlyphCache) {
+    use graphics::*;
+    use constants::boss_constants::*;
+
+    let diameter = config.boss.diameter;
+
+    let circle = rectangle::square(0.0, 0.0, diameter);
+    let square = rectangle::square(0.0, 0.0, GUND);
+
+    // create transform matrix
+    let transform = c.transform
+        .trans(boss.get_x(), boss.get_y())
+        .trans(-diameter / 2.0, -diameter / 2.0);
+
+    // Draw the boss
+    ellipse(GREEN, circle, transform, gl);
+
+    // Draw where the bullets where go
+    for x in 0..config.boss.volley_size {
+        let rot = boss.get_gun_rotation(x, config);
+        let transform2 = c.transform
+            .trans(boss.get_x(), boss.get_y())
+            .rot_rad(rot)
+            .trans(diameter / 2.0 - GUND / 2.0, -GUND / 2.0);
+        rectangle(PINK, square, transform2, gl);
+    }
+
+    // display the health
+    text(WHITE,
+         FONTSIZE,
+         format!("{}", boss.get_health()).as_str(),
+         glyph_cache,
+         c.transform.trans(boss.get_x(), boss.get_y()),
+         gl);
+}
+
+/// Draws a bullet
+fn draw_bullet(bullet: &Bullet,
+               alpha: f64,
+               config: &Config,
+               c: graphics::Context,
+               gl: &mut GlGraphics) {
+    use graphics::*;
+
+    let pos = bullet.get_prev_pos().lerp(&bullet.get_pos(), alpha);
+    let side_length = config.bullet.side_length;
+
+    let square = rectangle::square(0.0, 0.0, side_length);
+
+    // get color of bullet
+    let color = if bullet.get_friendly() { LIGHTBLUE } else { RED };
+
+    // create transform matrix
+    let transform = c.transform
+        .trans(pos.x, pos.y)
+        .rot_rad(bullet.get_rotation())
+        .trans(-side_length / 2.0, -side_length / 2.0);
+
+    // Draw a box rotating around the middle of the screen.
+    rectangle(color, square, transform, gl);
+}
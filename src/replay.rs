@@ -0,0 +1,387 @@
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use config::Config;
+use config::difficulty::Difficulty;
+use controller::Controller;
+use script::Script;
+use simulation::{Input, Simulation};
+
+/// Version of the file format. Version 2 added the difficulty, config and
+/// levels lines and the move, aim and dash inputs. Files of later versions
+/// are not read
+pub const VERSION: u32 = 2;
+
+/// Start of the first line of every replay file
+const HEADER: &str = "hit_n_run replay";
+
+/// Everything needed to reproduce a game: the seed, the starting size, the
+/// difficulty and every input along with the tick it was applied on. The
+/// config and levels aren't stored, only hashes of them to tell whether a
+/// replay is played back with the ones it was recorded with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    /// Seed the game started with
+    pub seed: u64,
+    /// Dimensions of the game screen when the game started
+    pub dimensions: [f64; 2],
+    /// Difficulty the game was played on
+    pub difficulty: Difficulty,
+    /// Hash of the config the game was played with, before the difficulty
+    /// scaled it. `None` when it wasn't recorded
+    pub config_hash: Option<u64>,
+    /// Hash of the levels the game was played with. `None` when it wasn't
+    /// recorded
+    pub levels_hash: Option<u64>,
+    /// Inputs in the order they were applied
+    pub inputs: Vec<(u64, Input)>,
+}
+
+/// Error returned when a replay can't be read
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// A line of the file is malformed
+    Parse { line: usize, message: String },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ReplayError::Io(ref e) => write!(f, "could not read replay: {}", e),
+            ReplayError::Parse { line, ref message } => {
+                write!(f, "replay line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for ReplayError {
+    fn from(e: io::Error) -> Self {
+        ReplayError::Io(e)
+    }
+}
+
+impl Replay {
+    /// Returns an empty replay
+    pub fn new(seed: u64, width: f64, height: f64) -> Self {
+        Replay {
+            seed,
+            dimensions: [width, height],
+            difficulty: Difficulty::Normal,
+            config_hash: None,
+            levels_hash: None,
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Records the config and levels the game is played with
+    pub fn set_sources(&mut self, config: &Config, script: &Script) {
+        self.config_hash = Some(fingerprint(config));
+        self.levels_hash = Some(fingerprint(script));
+    }
+
+    /// Returns what the replay was recorded with that differs from `config`
+    /// and `script`, like `["config"]`. A replay played back with something
+    /// else doesn't play out the same. Hashes that weren't recorded match
+    /// anything
+    pub fn get_mismatches(&self, config: &Config, script: &Script) -> Vec<&'static str> {
+        let mut mismatches = Vec::new();
+        if self.config_hash.is_some_and(|h| h != fingerprint(config)) {
+            mismatches.push("config");
+        }
+        if self.levels_hash.is_some_and(|h| h != fingerprint(script)) {
+            mismatches.push("levels");
+        }
+        mismatches
+    }
+
+    /// Adds the inputs applied on `tick` to the replay
+    pub fn record(&mut self, tick: u64, input: &[Input]) {
+        for i in input {
+            self.inputs.push((tick, *i));
+        }
+    }
+
+    /// Writes the replay to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write(&mut out)?;
+        out.flush()
+    }
+
+    /// Writes the replay. f64s are printed in their shortest form that reads
+    /// back to the exact same value.
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        self.write_header(out)?;
+        for &(tick, ref input) in &self.inputs {
+            write_input(out, tick, input)?;
+        }
+        Ok(())
+    }
+
+    /// Writes everything but the inputs
+    fn write_header<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        writeln!(out, "{} {}", HEADER, VERSION)?;
+        writeln!(out, "seed {}", self.seed)?;
+        writeln!(out, "size {} {}", self.dimensions[0], self.dimensions[1])?;
+        writeln!(out, "difficulty {}", self.difficulty.get_name())?;
+        if let Some(hash) = self.config_hash {
+            writeln!(out, "config {:016x}", hash)?;
+        }
+        if let Some(hash) = self.levels_hash {
+            writeln!(out, "levels {:016x}", hash)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a replay from a file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        Replay::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads a replay
+    pub fn read<R: BufRead>(input: R) -> Result<Self, ReplayError> {
+        let mut lines = input.lines();
+        let mut line_num = 0;
+
+        // reads the next line and splits it into words
+        let mut next_line = || -> Result<Option<(usize, Vec<String>)>, ReplayError> {
+            match lines.next() {
+                Some(line) => {
+                    line_num += 1;
+                    let words = line?.split_whitespace().map(String::from).collect();
+                    Ok(Some((line_num, words)))
+                }
+                None => Ok(None),
+            }
+        };
+
+        let (_, header) = next_line()?.ok_or_else(|| parse_error(1, "empty file"))?;
+        match header.split_last() {
+            Some((version, name)) if name.join(" ") == HEADER => {
+                match version.parse::<u32>() {
+                    Ok(v) if (1..=VERSION).contains(&v) => {}
+                    _ => return Err(parse_error(1, &format!("unknown version: {}", version))),
+                }
+            }
+            _ => return Err(parse_error(1, "not a replay file")),
+        }
+
+        let seed = match next_line()? {
+            Some((n, ref words)) if words.len() == 2 && words[0] == "seed" => {
+                parse(n, &words[1])?
+            }
+            Some((n, _)) => return Err(parse_error(n, "expected seed")),
+            None => return Err(parse_error(2, "expected seed")),
+        };
+
+        let mut replay = match next_line()? {
+            Some((n, ref words)) if words.len() == 3 && words[0] == "size" => {
+                Replay::new(seed, parse(n, &words[1])?, parse(n, &words[2])?)
+            }
+            Some((n, _)) => return Err(parse_error(n, "expected size")),
+            None => return Err(parse_error(3, "expected size")),
+        };
+
+        while let Some((n, words)) = next_line()? {
+            if words.is_empty() {
+                continue;
+            }
+            // the lines after the size are optional. Replays from before
+            // difficulties were played on normal
+            if words.len() == 2 && replay.inputs.is_empty() {
+                match words[0].as_str() {
+                    "difficulty" => {
+                        replay.difficulty = Difficulty::parse(&words[1]).ok_or_else(|| {
+                            parse_error(n, &format!("unknown difficulty: {}", words[1]))
+                        })?;
+                        continue;
+                    }
+                    "config" => {
+                        replay.config_hash = Some(parse_hash(n, &words[1])?);
+                        continue;
+                    }
+                    "levels" => {
+                        replay.levels_hash = Some(parse_hash(n, &words[1])?);
+                        continue;
+                    }
+                    _ => {}
+                }
+            }
+            if words.len() < 2 {
+                return Err(parse_error(n, "expected a tick and an input"));
+            }
+
+            let tick: u64 = parse(n, &words[0])?;
+            if replay.inputs.last().is_some_and(|&(last, _)| tick < last) {
+                return Err(parse_error(n, "ticks must not go backwards"));
+            }
+
+            let input = match (words[1].as_str(), words.len()) {
+                ("target", 4) => Input::Target(parse(n, &words[2])?, parse(n, &words[3])?),
+                ("move", 4) => Input::Move(parse(n, &words[2])?, parse(n, &words[3])?),
+                ("aim", 4) => Input::Aim(parse(n, &words[2])?, parse(n, &words[3])?),
+                ("shoot", 3) => Input::Shoot(parse(n, &words[2])?),
+                ("dash", 2) => Input::Dash,
+                ("reset", 2) => Input::Reset,
+                ("resize", 4) => Input::Resize(parse(n, &words[2])?, parse(n, &words[3])?),
+                _ => return Err(parse_error(n, &format!("unknown input: {}", words[1..].join(" ")))),
+            };
+            replay.inputs.push((tick, input));
+        }
+
+        Ok(replay)
+    }
+}
+
+/// Writes an input applied on `tick` as a line of a replay
+fn write_input<W: Write>(out: &mut W, tick: u64, input: &Input) -> io::Result<()> {
+    match *input {
+        Input::Target(x, y) => writeln!(out, "{} target {} {}", tick, x, y),
+        Input::Move(x, y) => writeln!(out, "{} move {} {}", tick, x, y),
+        Input::Aim(x, y) => writeln!(out, "{} aim {} {}", tick, x, y),
+        Input::Shoot(b) => writeln!(out, "{} shoot {}", tick, b),
+        Input::Dash => writeln!(out, "{} dash", tick),
+        Input::Reset => writeln!(out, "{} reset", tick),
+        Input::Resize(w, h) => writeln!(out, "{} resize {} {}", tick, w, h),
+    }
+}
+
+/// Reads a hash written in hex
+fn parse_hash(line: usize, word: &str) -> Result<u64, ReplayError> {
+    u64::from_str_radix(word, 16).map_err(|_| parse_error(line, &format!("invalid hash: {}", word)))
+}
+
+/// Returns a hash of everything in `value`, taken over its `Debug` output
+/// with FNV-1a. Unlike the hasher of the standard library FNV-1a is the same
+/// on every run, but `Debug` output may change with the compiler or the
+/// types, so a replay made by another build can warn about a config that
+/// didn't change
+fn fingerprint<T: fmt::Debug>(value: &T) -> u64 {
+    format!("{:?}", value)
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, b| (hash ^ u64::from(b)).wrapping_mul(0x100_0000_01b3))
+}
+
+/// Returns a parse error for a line
+fn parse_error(line: usize, message: &str) -> ReplayError {
+    ReplayError::Parse {
+        line,
+        message: message.to_string(),
+    }
+}
+
+/// Parses a single word of a line
+fn parse<T: ::std::str::FromStr>(line: usize, word: &str) -> Result<T, ReplayError> {
+    word.parse().map_err(|_| parse_error(line, &format!("invalid value: {}", word)))
+}
+
+/// Records the input of a game. Inputs are written to the file as they are
+/// recorded, so a crash doesn't lose the game that led up to it
+pub struct Recorder {
+    /// Where the replay is saved
+    path: PathBuf,
+    /// Seed, size and difficulty of the game. Inputs aren't kept in it
+    replay: Replay,
+    /// The file, once the first step was recorded
+    out: Option<BufWriter<File>>,
+}
+
+impl Recorder {
+    /// Returns a recorder for a game started with `seed` at the given size.
+    /// The file is created when the first step is recorded
+    pub fn new(path: PathBuf, seed: u64, width: f64, height: f64) -> Self {
+        Recorder {
+            path,
+            replay: Replay::new(seed, width, height),
+            out: None,
+        }
+    }
+
+    /// Records that the game is played on `difficulty`. Has to be called
+    /// before the first step is recorded
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.replay.difficulty = difficulty;
+    }
+
+    /// Records the config and levels the game is played with. Has to be
+    /// called before the first step is recorded
+    pub fn set_sources(&mut self, config: &Config, script: &Script) {
+        self.replay.set_sources(config, script);
+    }
+
+    /// Writes the inputs applied on `tick` to the file
+    pub fn record(&mut self, tick: u64, input: &[Input]) -> io::Result<()> {
+        let out = self.open()?;
+        for i in input {
+            write_input(out, tick, i)?;
+        }
+        out.flush()
+    }
+
+    /// Makes sure the file holds everything recorded, even if nothing was
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.open()?.flush()
+    }
+
+    /// Returns the file, creating it and writing the header the first time
+    fn open(&mut self) -> io::Result<&mut BufWriter<File>> {
+        if self.out.is_none() {
+            let mut out = BufWriter::new(File::create(&self.path)?);
+            self.replay.write_header(&mut out)?;
+            self.out = Some(out);
+        }
+        Ok(self.out.as_mut().expect("replay file is open"))
+    }
+
+    /// Returns the path the replay is saved to
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+}
+
+/// Plays back the inputs of a replay tick by tick
+pub struct Playback {
+    /// Replay being played
+    replay: Replay,
+    /// Index of the next input to play
+    next: usize,
+}
+
+impl Playback {
+    /// Returns a playback starting at the first input
+    pub fn new(replay: Replay) -> Self {
+        Playback { replay, next: 0 }
+    }
+
+    /// Returns whether every input has been played
+    pub fn finished(&self) -> bool {
+        self.next >= self.replay.inputs.len()
+    }
+
+    /// Returns the replay being played
+    pub fn get_replay(&self) -> &Replay {
+        &self.replay
+    }
+}
+
+/// Plays the inputs that were applied on the tick the simulation is on.
+/// Inputs recorded for earlier ticks that were never asked for are played
+/// as well.
+impl Controller for Playback {
+    fn inputs(&mut self, sim: &Simulation, input: &mut Vec<Input>) {
+        let tick = sim.get_tick();
+        while let Some(&(t, i)) = self.replay.inputs.get(self.next) {
+            if t > tick {
+                break;
+            }
+            input.push(i);
+            self.next += 1;
+        }
+    }
+}
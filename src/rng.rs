@@ -0,0 +1,66 @@
+extern crate rand;
+
+use rand::{Rng, SeedableRng};
+
+/// Random number generator used by the game. A xorshift generator so that the
+/// same seed gives the same game on every platform.
+#[derive(Clone, Debug)]
+pub struct GameRng {
+    /// Internal state. Never all zeros
+    state: [u32; 4],
+}
+
+impl GameRng {
+    /// Returns a generator seeded with `seed`
+    pub fn new(seed: u64) -> Self {
+        // spread the seed over the whole state with splitmix64
+        let mut x = seed;
+        let mut next = || {
+            x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        };
+        let a = next();
+        let b = next();
+
+        GameRng::from_state([a as u32, (a >> 32) as u32, b as u32, (b >> 32) as u32])
+    }
+
+    /// Returns a generator that continues from `state`
+    pub fn from_state(state: [u32; 4]) -> Self {
+        // xorshift gets stuck on an all zero state
+        if state == [0; 4] {
+            GameRng { state: [0x9E37_79B9, 0, 0, 0] }
+        } else {
+            GameRng { state }
+        }
+    }
+
+    /// Returns the internal state of the generator
+    pub fn get_state(&self) -> [u32; 4] {
+        self.state
+    }
+}
+
+impl Rng for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        let t = self.state[0] ^ (self.state[0] << 11);
+        self.state[0] = self.state[1];
+        self.state[1] = self.state[2];
+        self.state[2] = self.state[3];
+        self.state[3] = self.state[3] ^ (self.state[3] >> 19) ^ (t ^ (t >> 8));
+        self.state[3]
+    }
+}
+
+impl SeedableRng<u64> for GameRng {
+    fn reseed(&mut self, seed: u64) {
+        *self = GameRng::new(seed);
+    }
+
+    fn from_seed(seed: u64) -> Self {
+        GameRng::new(seed)
+    }
+}
@@ -0,0 +1,370 @@
+//! Levels read from data files. A script lists what spawns in every level
+//! and how many kills it takes to finish it. The game ships with
+//! `levels/default.toml`, which is also built in as `Script::default()`.
+
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use config::{at_least_one, boolean, count, non_negative};
+use config::toml;
+use config::toml::{Table, Value};
+
+/// Script played when no other one is given
+const DEFAULT: &str = include_str!("../levels/default.toml");
+
+/// What is spawned
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Kind {
+    /// Enemy that chases the player 1 in 4 times
+    Enemy,
+    /// Enemy that always chases the player
+    Chaser,
+    /// Enemy that never moves
+    Turret,
+    /// Boss
+    Boss,
+}
+
+/// Where things are spawned. Positions are fractions of the screen so a
+/// script works for any window size
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Area {
+    /// Anywhere in a rectangle
+    Region { x: f64, y: f64, width: f64, height: f64 },
+    /// An exact spot
+    Point { x: f64, y: f64 },
+}
+
+/// A group of things spawned during a level
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Spawn {
+    pub kind: Kind,
+    /// How many are spawned
+    pub count: u32,
+    pub area: Area,
+    /// Seconds after the level starts that the first one spawns
+    pub delay: f64,
+    /// Seconds between each one spawning
+    pub interval: f64,
+}
+
+impl Default for Spawn {
+    fn default() -> Self {
+        Spawn {
+            kind: Kind::Enemy,
+            count: 1,
+            area: Area::Region {
+                x: 0.0,
+                y: 0.0,
+                width: 1.0,
+                height: 1.0,
+            },
+            delay: 0.0,
+            interval: 0.0,
+        }
+    }
+}
+
+/// A level of the game
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    /// Kills needed to move on to the next level
+    pub kill_target: u32,
+    /// Whether enemies left over from the last level are removed
+    pub clear_enemies: bool,
+    /// Enemies spawned anywhere on screen for every kill
+    pub reinforcements: u32,
+    /// What spawns during the level
+    pub spawns: Vec<Spawn>,
+}
+
+/// How the last level grows every time it is played again
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Endless {
+    /// Added to the kill target
+    pub kill_target: u32,
+    /// Added to the count of every enemy, chaser and turret spawn
+    pub enemies: u32,
+    /// Added to the count of every boss spawn
+    pub bosses: u32,
+}
+
+/// Every level of a game
+#[derive(Clone, Debug, PartialEq)]
+pub struct Script {
+    /// Levels in the order they are played. Never empty
+    pub levels: Vec<Level>,
+    pub endless: Endless,
+}
+
+/// Error returned when a script can't be used
+#[derive(Debug)]
+pub enum ScriptError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// The file isn't valid TOML
+    Syntax(toml::SyntaxError),
+    /// A table that scripts don't have
+    UnknownTable { line: usize, table: String },
+    /// A `[[level.spawn]]` before the first `[[level]]`
+    Misplaced { line: usize },
+    /// A key that scripts don't have. Holds the full name, like
+    /// `level.kill_target`
+    UnknownKey { line: usize, key: String },
+    /// A key that has to be given but wasn't
+    MissingKey { line: usize, key: String },
+    /// A key whose value can't be used
+    InvalidValue { line: usize, key: String, message: String },
+    /// The script has no levels
+    NoLevels,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ScriptError::Io(ref e) => write!(f, "could not read levels: {}", e),
+            ScriptError::Syntax(ref e) => write!(f, "levels {}", e),
+            ScriptError::UnknownTable { line, ref table } => {
+                write!(f, "levels line {}: unknown table [{}]", line, table)
+            }
+            ScriptError::Misplaced { line } => {
+                write!(f, "levels line {}: [[level.spawn]] must come after a [[level]]", line)
+            }
+            ScriptError::UnknownKey { line, ref key } => {
+                write!(f, "levels line {}: unknown key {}", line, key)
+            }
+            ScriptError::MissingKey { line, ref key } => {
+                write!(f, "levels line {}: missing key {}", line, key)
+            }
+            ScriptError::InvalidValue { line, ref key, ref message } => {
+                write!(f, "levels line {}: {}: {}", line, key, message)
+            }
+            ScriptError::NoLevels => write!(f, "levels: there has to be at least one [[level]]"),
+        }
+    }
+}
+
+impl From<io::Error> for ScriptError {
+    fn from(e: io::Error) -> Self {
+        ScriptError::Io(e)
+    }
+}
+
+/// Returns the kind named by a string
+fn kind(value: &Value) -> Result<Kind, String> {
+    match value.as_str() {
+        Some("enemy") => Ok(Kind::Enemy),
+        Some("chaser") => Ok(Kind::Chaser),
+        Some("turret") => Ok(Kind::Turret),
+        Some("boss") => Ok(Kind::Boss),
+        Some(other) => Err(format!("unknown kind: {}", other)),
+        None => Err("expected a string".to_string()),
+    }
+}
+
+/// Returns an array of `len` fractions of the screen
+fn fractions(value: &Value, len: usize) -> Result<Vec<f64>, String> {
+    let error = || format!("expected an array of {} numbers from 0 to 1", len);
+    let array = value.as_array().ok_or_else(error)?;
+    if array.len() != len {
+        return Err(error());
+    }
+    array.iter()
+        .map(|v| match v.as_f64() {
+            Some(f) if (0.0..=1.0).contains(&f) => Ok(f),
+            _ => Err(error()),
+        })
+        .collect()
+}
+
+/// Returns a rectangle that is inside the screen and isn't empty
+fn region(value: &Value) -> Result<Area, String> {
+    let r = fractions(value, 4)?;
+    if r[2] <= 0.0 || r[3] <= 0.0 {
+        return Err("width and height must be greater than 0".to_string());
+    }
+    // allow for rounding, 0.7 + 0.3 isn't quite 1
+    if r[0] + r[2] > 1.0 + 1e-9 || r[1] + r[3] > 1.0 + 1e-9 {
+        return Err("must be inside the screen".to_string());
+    }
+    Ok(Area::Region {
+        x: r[0],
+        y: r[1],
+        width: r[2],
+        height: r[3],
+    })
+}
+
+/// Returns a spot on the screen
+fn point(value: &Value) -> Result<Area, String> {
+    let p = fractions(value, 2)?;
+    Ok(Area::Point { x: p[0], y: p[1] })
+}
+
+impl Script {
+    /// Reads a script file
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ScriptError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Script::parse(&text)
+    }
+
+    /// Reads a script from text
+    pub fn parse(text: &str) -> Result<Self, ScriptError> {
+        let mut levels: Vec<Level> = Vec::new();
+        let mut endless = Endless::default();
+
+        for table in toml::parse(text).map_err(ScriptError::Syntax)? {
+            match (table.name.as_str(), table.array) {
+                ("", false) => {
+                    if let Some(entry) = table.entries.first() {
+                        return Err(ScriptError::UnknownKey {
+                            line: entry.line,
+                            key: entry.key.clone(),
+                        });
+                    }
+                }
+                ("endless", false) => endless = parse_endless(&table)?,
+                ("level", true) => levels.push(parse_level(&table)?),
+                ("level.spawn", true) => {
+                    let spawn = parse_spawn(&table)?;
+                    match levels.last_mut() {
+                        Some(level) => level.spawns.push(spawn),
+                        None => return Err(ScriptError::Misplaced { line: table.line }),
+                    }
+                }
+                _ => {
+                    return Err(ScriptError::UnknownTable {
+                        line: table.line,
+                        table: table.name,
+                    })
+                }
+            }
+        }
+
+        if levels.is_empty() {
+            return Err(ScriptError::NoLevels);
+        }
+        Ok(Script { levels, endless })
+    }
+
+    /// Returns a level, starting at 1. Levels past the end of the script are
+    /// the last level grown by `endless` once for every level past it
+    pub fn get_level(&self, number: u32) -> Level {
+        let last = self.levels.len() as u32;
+        if number >= 1 && number <= last {
+            return self.levels[number as usize - 1].clone();
+        }
+
+        let extra = number.saturating_sub(last);
+        let grow = |value: u32, step: u32| value.saturating_add(step.saturating_mul(extra));
+        let mut level = self.levels[last as usize - 1].clone();
+        level.kill_target = grow(level.kill_target, self.endless.kill_target);
+        for spawn in &mut level.spawns {
+            let step = match spawn.kind {
+                Kind::Boss => self.endless.bosses,
+                _ => self.endless.enemies,
+            };
+            spawn.count = grow(spawn.count, step);
+        }
+        level
+    }
+}
+
+/// The built in script, which plays like `levels/default.toml`
+impl Default for Script {
+    fn default() -> Self {
+        Script::parse(DEFAULT).expect("default levels are invalid")
+    }
+}
+
+/// Calls `set` for every entry of a table, turning its errors into script
+/// errors. `set` returns None for keys it doesn't know
+fn parse_entries<F>(table: &Table, mut set: F) -> Result<(), ScriptError>
+    where F: FnMut(&str, &Value) -> Option<Result<(), String>>
+{
+    for entry in &table.entries {
+        let key = format!("{}.{}", table.name, entry.key);
+        match set(&entry.key, &entry.value) {
+            Some(Ok(())) => {}
+            Some(Err(message)) => {
+                return Err(ScriptError::InvalidValue {
+                    line: entry.line,
+                    key,
+                    message,
+                })
+            }
+            None => return Err(ScriptError::UnknownKey { line: entry.line, key }),
+        }
+    }
+    Ok(())
+}
+
+/// Reads an `[endless]` table
+fn parse_endless(table: &Table) -> Result<Endless, ScriptError> {
+    let mut endless = Endless::default();
+    parse_entries(table, |key, value| {
+        Some(match key {
+            "kill_target" => count(value).map(|v| endless.kill_target = v),
+            "enemies" => count(value).map(|v| endless.enemies = v),
+            "bosses" => count(value).map(|v| endless.bosses = v),
+            _ => return None,
+        })
+    })?;
+    Ok(endless)
+}
+
+/// Reads a `[[level]]` table. Its spawns are added afterwards
+fn parse_level(table: &Table) -> Result<Level, ScriptError> {
+    let mut kill_target = None;
+    let mut level = Level {
+        kill_target: 1,
+        clear_enemies: true,
+        reinforcements: 2,
+        spawns: Vec::new(),
+    };
+    parse_entries(table, |key, value| {
+        Some(match key {
+            "kill_target" => at_least_one(value).map(|v| kill_target = Some(v)),
+            "clear_enemies" => boolean(value).map(|v| level.clear_enemies = v),
+            "reinforcements" => count(value).map(|v| level.reinforcements = v),
+            _ => return None,
+        })
+    })?;
+
+    level.kill_target = kill_target.ok_or_else(|| {
+            ScriptError::MissingKey {
+                line: table.line,
+                key: "level.kill_target".to_string(),
+            }
+        })?;
+    Ok(level)
+}
+
+/// Reads a `[[level.spawn]]` table
+fn parse_spawn(table: &Table) -> Result<Spawn, ScriptError> {
+    let mut spawn = Spawn::default();
+    let mut placed = false;
+    parse_entries(table, |key, value| {
+        Some(match key {
+            "kind" => kind(value).map(|v| spawn.kind = v),
+            "count" => count(value).map(|v| spawn.count = v),
+            "delay" => non_negative(value).map(|v| spawn.delay = v),
+            "interval" => non_negative(value).map(|v| spawn.interval = v),
+            "region" | "at" if placed => Err("only one of region and at can be given".to_string()),
+            "region" => region(value).map(|v| {
+                spawn.area = v;
+                placed = true;
+            }),
+            "at" => point(value).map(|v| {
+                spawn.area = v;
+                placed = true;
+            }),
+            _ => return None,
+        })
+    })?;
+    Ok(spawn)
+}
@@ -0,0 +1,343 @@
+//! Preferences of the person playing that don't change the game itself, like
+//! which keys and mouse buttons do what. Read from `settings.toml`.
+
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+use piston::input::{Button, Key, MouseButton};
+
+use config::toml;
+use config::toml::Value;
+
+/// Something a button can be bound to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Shoot while the button is held
+    Fire,
+    /// Start a new game
+    Reset,
+    /// Stop or resume the game
+    Pause,
+    /// Make the player dash
+    Dash,
+    /// Save the game to the quicksave file
+    Quicksave,
+    /// Load the game from the quicksave file
+    Quickload,
+    /// Move up while the button is held, when moving with the keyboard
+    MoveUp,
+    /// Move down while the button is held, when moving with the keyboard
+    MoveDown,
+    /// Move left while the button is held, when moving with the keyboard
+    MoveLeft,
+    /// Move right while the button is held, when moving with the keyboard
+    MoveRight,
+    /// Show or hide the debug overlay
+    Debug,
+}
+
+impl Action {
+    /// Every action, in the order they are listed in settings files
+    pub const ALL: [Action; 11] = [Action::Fire,
+                                   Action::Reset,
+                                   Action::Pause,
+                                   Action::Dash,
+                                   Action::Quicksave,
+                                   Action::Quickload,
+                                   Action::MoveUp,
+                                   Action::MoveDown,
+                                   Action::MoveLeft,
+                                   Action::MoveRight,
+                                   Action::Debug];
+
+    /// Returns the name of the action in settings files
+    pub fn get_name(&self) -> &'static str {
+        match *self {
+            Action::Fire => "fire",
+            Action::Reset => "reset",
+            Action::Pause => "pause",
+            Action::Dash => "dash",
+            Action::Quicksave => "quicksave",
+            Action::Quickload => "quickload",
+            Action::MoveUp => "up",
+            Action::MoveDown => "down",
+            Action::MoveLeft => "left",
+            Action::MoveRight => "right",
+            Action::Debug => "debug",
+        }
+    }
+
+    /// Returns the direction a movement action moves in, on a screen whose
+    /// y axis points down
+    pub fn get_direction(&self) -> Option<(f64, f64)> {
+        match *self {
+            Action::MoveUp => Some((0.0, -1.0)),
+            Action::MoveDown => Some((0.0, 1.0)),
+            Action::MoveLeft => Some((-1.0, 0.0)),
+            Action::MoveRight => Some((1.0, 0.0)),
+            _ => None,
+        }
+    }
+}
+
+/// How the player is moved
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Movement {
+    /// The player follows the mouse and shoots the way it moves
+    #[default]
+    Mouse,
+    /// The movement keys move the player and it aims at the mouse
+    Keyboard,
+}
+
+/// Which buttons do which action. A button does at most one action
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    /// Every bound button and its action
+    buttons: Vec<(Button, Action)>,
+}
+
+impl Bindings {
+    /// Returns bindings without any buttons
+    pub fn empty() -> Self {
+        Bindings { buttons: Vec::new() }
+    }
+
+    /// Returns the action of a button
+    pub fn get_action(&self, button: Button) -> Option<Action> {
+        self.buttons.iter().find(|&&(b, _)| b == button).map(|&(_, a)| a)
+    }
+
+    /// Returns the buttons bound to an action
+    pub fn get_buttons(&self, action: Action) -> Vec<Button> {
+        self.buttons.iter().filter(|&&(_, a)| a == action).map(|&(b, _)| b).collect()
+    }
+
+    /// Returns the name of the first button of an action, as it is written
+    /// in settings files
+    pub fn get_button_name(&self, action: Action) -> String {
+        match self.get_buttons(action).first() {
+            Some(&Button::Mouse(button)) => format!("Mouse{:?}", button),
+            Some(&Button::Keyboard(key)) => format!("{:?}", key),
+            Some(button) => format!("{:?}", button),
+            None => "unbound".to_string(),
+        }
+    }
+
+    /// Binds a button to an action, taking it away from any other action
+    pub fn bind(&mut self, button: Button, action: Action) {
+        self.buttons.retain(|&(b, _)| b != button);
+        self.buttons.push((button, action));
+    }
+
+    /// Removes every button of an action
+    pub fn unbind(&mut self, action: Action) {
+        self.buttons.retain(|&(_, a)| a != action);
+    }
+}
+
+/// Space or the left mouse button fires, right mouse button or left shift
+/// dashes, R resets, P pauses, F5 quicksaves, F9 quickloads and F3 shows the
+/// debug overlay. WASD and the arrow keys move
+impl Default for Bindings {
+    fn default() -> Self {
+        let mut bindings = Bindings::empty();
+        bindings.bind(Button::Keyboard(Key::Space), Action::Fire);
+        bindings.bind(Button::Mouse(MouseButton::Left), Action::Fire);
+        bindings.bind(Button::Keyboard(Key::R), Action::Reset);
+        bindings.bind(Button::Keyboard(Key::P), Action::Pause);
+        bindings.bind(Button::Keyboard(Key::LShift), Action::Dash);
+        bindings.bind(Button::Mouse(MouseButton::Right), Action::Dash);
+        bindings.bind(Button::Keyboard(Key::F5), Action::Quicksave);
+        bindings.bind(Button::Keyboard(Key::F9), Action::Quickload);
+        bindings.bind(Button::Keyboard(Key::W), Action::MoveUp);
+        bindings.bind(Button::Keyboard(Key::Up), Action::MoveUp);
+        bindings.bind(Button::Keyboard(Key::S), Action::MoveDown);
+        bindings.bind(Button::Keyboard(Key::Down), Action::MoveDown);
+        bindings.bind(Button::Keyboard(Key::A), Action::MoveLeft);
+        bindings.bind(Button::Keyboard(Key::Left), Action::MoveLeft);
+        bindings.bind(Button::Keyboard(Key::D), Action::MoveRight);
+        bindings.bind(Button::Keyboard(Key::Right), Action::MoveRight);
+        bindings.bind(Button::Keyboard(Key::F3), Action::Debug);
+        bindings
+    }
+}
+
+/// Every setting
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Settings {
+    pub movement: Movement,
+    pub bindings: Bindings,
+}
+
+/// Error returned when a settings file can't be used
+#[derive(Debug)]
+pub enum SettingsError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// The file isn't valid TOML
+    Syntax(toml::SyntaxError),
+    /// A table that the settings don't have
+    UnknownTable { line: usize, table: String },
+    /// A key that the settings don't have. Holds the full name, like
+    /// `bindings.fire`
+    UnknownKey { line: usize, key: String },
+    /// A key whose value can't be used
+    InvalidValue { line: usize, key: String, message: String },
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SettingsError::Io(ref e) => write!(f, "could not read settings: {}", e),
+            SettingsError::Syntax(ref e) => write!(f, "settings {}", e),
+            SettingsError::UnknownTable { line, ref table } => {
+                write!(f, "settings line {}: unknown table [{}]", line, table)
+            }
+            SettingsError::UnknownKey { line, ref key } => {
+                write!(f, "settings line {}: unknown key {}", line, key)
+            }
+            SettingsError::InvalidValue { line, ref key, ref message } => {
+                write!(f, "settings line {}: {}: {}", line, key, message)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for SettingsError {
+    fn from(e: io::Error) -> Self {
+        SettingsError::Io(e)
+    }
+}
+
+/// Returns the button with a name. Keys go by their name in piston, like
+/// `Space`, `R`, `F5` or `LShift`. Mouse buttons are `MouseLeft`,
+/// `MouseRight`, `MouseMiddle`, `MouseX1` and `MouseX2`. Case is ignored
+pub fn parse_button(name: &str) -> Option<Button> {
+    let lower = name.to_lowercase();
+
+    if lower.starts_with("mouse") {
+        let buttons = [MouseButton::Left,
+                       MouseButton::Right,
+                       MouseButton::Middle,
+                       MouseButton::X1,
+                       MouseButton::X2];
+        return buttons.iter()
+            .find(|b| format!("mouse{:?}", b).to_lowercase() == lower)
+            .map(|&b| Button::Mouse(b));
+    }
+
+    // keys follow SDL's keycodes, which are ascii or have bit 30 set
+    (0..0x80)
+        .chain(0x4000_0000..0x4000_0200)
+        .map(Key::from)
+        .find(|k| *k != Key::Unknown && format!("{:?}", k).to_lowercase() == lower)
+        .map(Button::Keyboard)
+}
+
+/// Returns the buttons named by a string or an array of strings
+fn buttons(value: &Value) -> Result<Vec<Button>, String> {
+    let names = match *value {
+        Value::String(_) => vec![value.clone()],
+        Value::Array(ref a) => a.clone(),
+        _ => return Err("expected a button or an array of buttons".to_string()),
+    };
+
+    names.iter()
+        .map(|name| {
+            let name = name.as_str().ok_or("expected a button name")?;
+            parse_button(name).ok_or_else(|| format!("unknown button: {}", name))
+        })
+        .collect()
+}
+
+impl Settings {
+    /// Reads a settings file. Settings that are left out keep their default
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SettingsError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Settings::parse(&text)
+    }
+
+    /// Reads settings from text. Settings that are left out keep their
+    /// default
+    pub fn parse(text: &str) -> Result<Self, SettingsError> {
+        let mut settings = Settings::default();
+
+        // actions bound in this file. Their buttons can't be taken by others
+        let mut bound: Vec<Action> = Vec::new();
+
+        for table in toml::parse(text).map_err(SettingsError::Syntax)? {
+            if table.name.is_empty() {
+                if let Some(entry) = table.entries.first() {
+                    return Err(SettingsError::UnknownKey {
+                        line: entry.line,
+                        key: entry.key.clone(),
+                    });
+                }
+                continue;
+            }
+            if !table.array && table.name == "controls" {
+                for entry in &table.entries {
+                    if entry.key != "movement" {
+                        return Err(SettingsError::UnknownKey {
+                            line: entry.line,
+                            key: format!("controls.{}", entry.key),
+                        });
+                    }
+                    settings.movement = match entry.value.as_str() {
+                        Some("mouse") => Movement::Mouse,
+                        Some("keyboard") => Movement::Keyboard,
+                        _ => {
+                            return Err(SettingsError::InvalidValue {
+                                line: entry.line,
+                                key: "controls.movement".to_string(),
+                                message: "expected \"mouse\" or \"keyboard\"".to_string(),
+                            })
+                        }
+                    };
+                }
+                continue;
+            }
+            if table.array || table.name != "bindings" {
+                return Err(SettingsError::UnknownTable {
+                    line: table.line,
+                    table: table.name,
+                });
+            }
+
+            for entry in &table.entries {
+                let key = format!("bindings.{}", entry.key);
+                let action = match Action::ALL.iter().find(|a| a.get_name() == entry.key) {
+                    Some(&action) => action,
+                    None => return Err(SettingsError::UnknownKey { line: entry.line, key }),
+                };
+                let invalid = |message: String| {
+                    SettingsError::InvalidValue {
+                        line: entry.line,
+                        key: key.clone(),
+                        message,
+                    }
+                };
+
+                let buttons = buttons(&entry.value).map_err(&invalid)?;
+                settings.bindings.unbind(action);
+                for button in buttons {
+                    if let Some(other) = settings.bindings.get_action(button) {
+                        if other != action && bound.contains(&other) {
+                            return Err(invalid(format!("button is already bound to {}",
+                                                       other.get_name())));
+                        }
+                    }
+                    settings.bindings.bind(button, action);
+                }
+                bound.push(action);
+            }
+        }
+
+        Ok(settings)
+    }
+}
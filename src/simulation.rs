@@ -0,0 +1,747 @@
+extern crate rand;
+
+use rand::Rng;
+
+use collision::Collider;
+use collision::grid::Grid;
+use config::Config;
+use config::difficulty::Difficulty;
+use constants::game_constants::GRIDCELL;
+use director::Director;
+use events::{DamageSource, Event};
+use rng::GameRng;
+use snapshot::{Reader, Snapshot, SnapshotError, Writer};
+use models::enemy::Enemy;
+use models::player::Player;
+use models::boss::Boss;
+use pool::Pool;
+use script::{Area, Kind, Level, Script, Spawn};
+use stats::RunStats;
+use weapons::bullet::Bullet;
+
+/// Input that can be applied to the simulation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Input {
+    /// Position the player should move towards
+    Target(f64, f64),
+    /// Direction the player should move in, used instead of a target. Its
+    /// length is the fraction of the full speed, at most one
+    Move(f64, f64),
+    /// Position the player aims at, whichever way it moves. Until it is
+    /// given the player aims the way it moves
+    Aim(f64, f64),
+    /// Whether the player is holding the fire button
+    Shoot(bool),
+    /// Makes the player dash
+    Dash,
+    /// Resets the game
+    Reset,
+    /// New dimensions of the game screen
+    Resize(f64, f64),
+}
+
+/// Contains the state of the game. Does not know anything about the window
+/// or the renderer so it can be stepped without a display.
+pub struct Simulation {
+    /// User controlled player
+    player: Player,
+    /// Enemies that have to be killed
+    enemies: Pool<Enemy>,
+    /// Bullets shot by the player
+    player_bullets: Pool<Bullet>,
+    /// Bullets shot by the enemy
+    enemy_bullets: Pool<Bullet>,
+    /// Dimensions of the game screen [width,height]
+    dimensions: [f64; 2],
+    /// Score of the game.
+    score: u32,
+    /// Current level of the game.
+    level: u32,
+    /// Kills made during this level
+    current_kills: u32,
+    /// Levels of the game. Not part of snapshots
+    script: Script,
+    /// Current level as given by the script
+    level_def: Level,
+    /// Seconds since the current level started
+    level_time: f64,
+    /// How many of each spawn of the current level have been spawned
+    spawned: Vec<u32>,
+    /// But did you die tho?
+    game_over: bool,
+    /// Bosses
+    bosses: Vec<Boss>,
+    /// Seed the current run started with
+    seed: u64,
+    /// Source of all randomness in the game
+    rng: GameRng,
+    /// Amount of steps taken since the simulation was created
+    tick: u64,
+    /// How the current run went so far
+    stats: RunStats,
+    /// Adjusts the game to how the player is doing
+    director: Director,
+    /// Gameplay values. Not part of snapshots
+    config: Config,
+    /// Difficulty the config was scaled to, so a saved game is loaded on it
+    difficulty: Difficulty,
+    /// Gameplay values as adjusted by the director
+    directed: Config,
+    /// Broadphase for collision checks, filled again for every check
+    grid: Grid,
+    /// Things the broadphase found near the thing being checked
+    candidates: Vec<usize>,
+    /// Events of the last step. Not part of snapshots
+    events: Vec<Event>,
+}
+
+impl Simulation {
+    /// Returns a simulation at the start of the first level
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The width of the game screen, at least 1
+    /// * `height` - The height of the game screen, at least 1
+    /// * `seed` - Seed for the random number generator. Same seed and input give the same game
+    /// * `config` - Gameplay values
+    /// * `script` - Levels of the game
+    pub fn new(width: f64, height: f64, seed: u64, config: Config, script: Script) -> Self {
+        let [width, height] = screen_size(width, height);
+        let mut sim = Simulation {
+            // player starts out at center of screen
+            player: Player::new(width / 2.0, height / 2.0, &config),
+            player_bullets: Pool::new(),
+            enemy_bullets: Pool::new(),
+            enemies: Pool::new(),
+            dimensions: [width, height],
+            score: 0,
+            level: 1,
+            current_kills: 0,
+            level_def: script.get_level(1),
+            script,
+            level_time: 0.0,
+            spawned: Vec::new(),
+            game_over: false,
+            bosses: Vec::<Boss>::new(),
+            seed,
+            rng: GameRng::new(seed),
+            tick: 0,
+            stats: RunStats::new(),
+            director: Director::new(),
+            directed: config.clone(),
+            config,
+            difficulty: Difficulty::Normal,
+            grid: Grid::new(GRIDCELL, &[width, height]),
+            candidates: Vec::new(),
+            events: Vec::new(),
+        };
+
+        sim.start_level();
+        sim
+    }
+
+    /// Advances the simulation
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Time in seconds since the last step
+    /// * `input` - Input received since the last step
+    pub fn step(&mut self, dt: f64, input: &[Input]) {
+        self.events.clear();
+
+        for i in input {
+            self.apply(i);
+        }
+
+        // nothing moves once the game is over
+        if !self.game_over {
+            self.stats.add_time(dt, self.player.get_burst_cooldown() > 0.0);
+            self.update(dt);
+        }
+        for event in &self.events {
+            self.stats.record(event, &self.config);
+            self.director.record(event);
+        }
+        // the director adjusts after it saw what happened in the step
+        if !self.game_over {
+            self.direct(dt);
+        }
+
+        self.tick += 1;
+    }
+
+    /// Lets the director adjust the game to how the player is doing
+    fn direct(&mut self, dt: f64) {
+        let health = f64::from(self.player.get_health()) /
+                     f64::from(self.config.player.start_health);
+        if let Some(rating) = self.director.update(dt, health, &self.config.director) {
+            self.directed = self.director.apply(&self.config);
+            let event = Event::DirectorAdjusted { rating };
+            self.stats.record(&event, &self.config);
+            self.events.push(event);
+        }
+    }
+
+    /// Applies a single input to the simulation
+    fn apply(&mut self, input: &Input) {
+        match *input {
+            Input::Target(x, y) => {
+                if !self.game_over {
+                    self.player.desired_update(x, y);
+                }
+            }
+            Input::Move(x, y) => {
+                if !self.game_over {
+                    self.player.direction_update(x, y);
+                }
+            }
+            Input::Aim(x, y) => {
+                if !self.game_over {
+                    self.player.aim_update(x, y);
+                }
+            }
+            Input::Shoot(true) => {
+                if !self.game_over {
+                    self.player.start_shooting();
+                }
+            }
+            Input::Shoot(false) => self.player.stop_shooting(),
+            Input::Dash => {
+                if !self.game_over && self.player.dash(&self.config) {
+                    self.events.push(Event::PlayerDashed);
+                }
+            }
+            Input::Reset => self.reset(),
+            Input::Resize(width, height) => self.resize(width, height),
+        }
+    }
+
+    /// Updates all the things in the game
+    fn update(&mut self, dt: f64) {
+        // update player, check if a bullet was shot
+        if let Some(bullet) = self.player.update(dt, &self.dimensions, &self.config) {
+            self.player_bullets.insert(bullet);
+            self.events.push(Event::PlayerFired);
+        }
+
+        for bullet in &mut self.enemy_bullets {
+            bullet.update(dt, &self.dimensions, &self.config);
+        }
+
+        // check for hit
+        self.grid.clear();
+        for (h, bullet) in self.enemy_bullets.handles() {
+            self.grid.insert_shape(h.get_index(), &bullet.get_swept_shape(&self.config));
+        }
+        self.grid.query_shape(&self.player.get_swept_shape(&self.config), &mut self.candidates);
+        for &i in &self.candidates {
+            if let Some(h) = self.enemy_bullets.handle(i) {
+                if self.player.hit(&mut self.enemy_bullets[h], &self.config) {
+                    self.events.push(Event::PlayerDamaged { source: DamageSource::Bullet });
+                }
+            }
+        }
+
+        // check if we should end the game
+        if !self.player.get_alive() {
+            self.game_over = true;
+            self.events.push(Event::GameOver {
+                score: self.score,
+                level: self.level,
+            });
+        }
+
+        for enemy in &mut self.enemies {
+            // update enemy, check for bullet
+            let shot = enemy.update(dt,
+                                    self.player.get_x(),
+                                    self.player.get_y(),
+                                    &self.dimensions,
+                                    &self.directed,
+                                    &mut self.rng);
+            if let Some(bullet) = shot {
+                self.enemy_bullets.insert(bullet);
+            }
+        }
+
+        // check if the player collided with an orb
+        self.grid.clear();
+        for (h, enemy) in self.enemies.handles() {
+            self.grid.insert_shape(h.get_index(), &enemy.get_orb().get_swept_shape(&self.config));
+        }
+        self.grid.query_shape(&self.player.get_swept_shape(&self.config), &mut self.candidates);
+        for &i in &self.candidates {
+            if let Some(h) = self.enemies.handle(i) {
+                if self.player.collide(&mut self.enemies[h], &self.config) {
+                    self.events.push(Event::PlayerDamaged { source: DamageSource::Orb });
+                }
+            }
+        }
+
+        for boss in &mut self.bosses {
+            // update the bosses
+            boss.update(dt, &self.directed, &mut self.enemy_bullets);
+        }
+
+        for bullet in &mut self.player_bullets {
+            bullet.update(dt, &self.dimensions, &self.config);
+        }
+
+        // check if an enemy was hit
+        self.grid.clear();
+        for (h, enemy) in self.enemies.handles() {
+            self.grid.insert_shape(h.get_index(), &enemy.get_swept_shape(&self.config));
+        }
+        for bullet in &mut self.player_bullets {
+            self.grid.query_shape(&bullet.get_swept_shape(&self.config), &mut self.candidates);
+            for &i in &self.candidates {
+                if let Some(h) = self.enemies.handle(i) {
+                    let enemy = &mut self.enemies[h];
+                    if enemy.hit(bullet, &self.config) && enemy.get_alive() {
+                        self.events.push(Event::EnemyHit { pos: enemy.get_pos() });
+                    }
+                }
+            }
+        }
+
+        // check if a boss was hit
+        self.grid.clear();
+        for (i, boss) in self.bosses.iter().enumerate() {
+            self.grid.insert_shape(i, &boss.get_swept_shape(&self.config));
+        }
+        for bullet in &mut self.player_bullets {
+            self.grid.query_shape(&bullet.get_swept_shape(&self.config), &mut self.candidates);
+            for &i in &self.candidates {
+                let boss = &mut self.bosses[i];
+                if boss.hit(bullet, &self.config) && boss.get_alive() {
+                    self.events.push(Event::BossHit { pos: boss.get_pos() });
+                }
+            }
+        }
+
+        // remove bullets that are no longer alive
+        self.player_bullets.retain(|e| e.get_alive());
+        self.enemy_bullets.retain(|e| e.get_alive());
+
+        // remove the dead, the kills are counted below
+        let first_kill = self.events.len();
+        {
+            let events = &mut self.events;
+            self.enemies.retain(|e| if e.get_alive() {
+                true
+            } else {
+                events.push(Event::EnemyKilled { pos: e.get_pos() });
+                false
+            });
+            self.bosses.retain(|e| if e.get_alive() {
+                true
+            } else {
+                events.push(Event::BossKilled { pos: e.get_pos() });
+                false
+            });
+        }
+
+        // how many enemies we will add for the kills
+        let mut enemies_to_add = 0;
+
+        for i in first_kill..self.events.len() {
+            match self.events[i] {
+                Event::EnemyKilled { .. } => {
+                    self.score += 1;
+                    self.current_kills += 1;
+                    enemies_to_add += self.get_reinforcements();
+                    self.player.increase_health(self.config.game.enemy_kill);
+                }
+                Event::BossKilled { .. } => {
+                    self.player.increase_health(self.config.game.boss_kill);
+                    self.score += 2;
+                }
+                _ => {}
+            }
+        }
+
+        // check if we have passed a level
+        if self.new_level() {
+            self.update_level();
+            self.start_level();
+        } else {
+            let anywhere = Spawn::default().area;
+            for _ in 0..enemies_to_add {
+                self.spawn(Kind::Enemy, anywhere);
+            }
+
+            self.level_time += dt;
+            self.spawn_due();
+        }
+    }
+
+    /// Sets up the current level and spawns what it starts with
+    fn start_level(&mut self) {
+        self.level_def = self.script.get_level(self.level);
+        self.level_time = 0.0;
+        self.spawned.clear();
+        self.spawned.resize(self.level_def.spawns.len(), 0);
+
+        if self.level_def.clear_enemies {
+            self.enemies.clear();
+        }
+        self.spawn_due();
+    }
+
+    /// Spawns everything of the current level whose time has come
+    fn spawn_due(&mut self) {
+        for i in 0..self.level_def.spawns.len() {
+            let spawn = self.level_def.spawns[i];
+            let count = match spawn.kind {
+                Kind::Boss => self.director.get_bosses(spawn.count, &self.config.director),
+                _ => spawn.count,
+            };
+            while self.spawned[i] < count &&
+                  spawn.delay + self.spawned[i] as f64 * spawn.interval <= self.level_time {
+                self.spawn(spawn.kind, spawn.area);
+                self.spawned[i] += 1;
+            }
+        }
+    }
+
+    /// Adds an enemy or boss somewhere in `area`
+    fn spawn(&mut self, kind: Kind, area: Area) {
+        let (width, height) = (self.dimensions[0], self.dimensions[1]);
+        let (x, y) = match area {
+            Area::Point { x, y } => (x * width, y * height),
+            Area::Region { x, y, width: w, height: h } => {
+                // get random x and y locations
+                (self.rng.gen_range(x * width, (x + w) * width),
+                 self.rng.gen_range(y * height, (y + h) * height))
+            }
+        };
+
+        let forward = match kind {
+            Kind::Boss => {
+                let boss = Boss::new(x, y, &self.config, &mut self.rng);
+                self.bosses.push(boss);
+                return;
+            }
+            // 1 / 4 chance of being able to move forward
+            Kind::Enemy => self.rng.gen_range(1, 5) == 1,
+            Kind::Chaser => true,
+            Kind::Turret => false,
+        };
+        let enemy = Enemy::new(x, y, forward, &self.config, &mut self.rng);
+        self.enemies.insert(enemy);
+    }
+
+    /// Updates the size of the game screen. A screen of no size, like that
+    /// of a minimized window, is taken to be 1 by 1
+    pub fn resize(&mut self, width: f64, height: f64) {
+        self.dimensions = screen_size(width, height);
+        self.grid.resize(&self.dimensions);
+    }
+
+    /// Resets the game. The next run gets a new seed drawn from the current
+    /// one so that a run can be reproduced from its own seed.
+    pub fn reset(&mut self) {
+        self.seed = self.rng.gen();
+        self.rng = GameRng::new(self.seed);
+        self.enemies.clear();
+        self.player_bullets.clear();
+        self.enemy_bullets.clear();
+        self.bosses.clear();
+        self.score = 0;
+        self.level = 1;
+        self.current_kills = 0;
+        self.stats = RunStats::new();
+        self.director = Director::new();
+        self.directed = self.config.clone();
+        self.player.reset(self.dimensions[0], self.dimensions[1], &self.config);
+        self.start_level();
+        self.game_over = false;
+        self.events.push(Event::GameReset { seed: self.seed });
+    }
+
+    /// Returns whether we have moved to new level
+    fn new_level(&self) -> bool {
+        self.current_kills >= self.level_def.kill_target
+    }
+
+    /// Update the level and reset current_kills
+    fn update_level(&mut self) {
+        self.level += 1;
+        self.current_kills = 0;
+        self.events.push(Event::LevelAdvanced { level: self.level });
+    }
+
+    /// Returns the player
+    pub fn get_player(&self) -> &Player {
+        &self.player
+    }
+
+    /// Returns the enemies
+    pub fn get_enemies(&self) -> &Pool<Enemy> {
+        &self.enemies
+    }
+
+    /// Returns the bosses
+    pub fn get_bosses(&self) -> &[Boss] {
+        &self.bosses
+    }
+
+    /// Returns the bullets shot by the player
+    pub fn get_player_bullets(&self) -> &Pool<Bullet> {
+        &self.player_bullets
+    }
+
+    /// Returns the bullets shot by enemies and bosses
+    pub fn get_enemy_bullets(&self) -> &Pool<Bullet> {
+        &self.enemy_bullets
+    }
+
+    /// Returns the dimensions of the game screen
+    pub fn get_dimensions(&self) -> [f64; 2] {
+        self.dimensions
+    }
+
+    /// Returns the current score
+    pub fn get_score(&self) -> u32 {
+        self.score
+    }
+
+    /// Returns the current level
+    pub fn get_level(&self) -> u32 {
+        self.level
+    }
+
+    /// Returns the enemies added for a kill on the current level, as
+    /// adjusted by the director
+    pub fn get_reinforcements(&self) -> u32 {
+        self.director.get_reinforcements(self.level_def.reinforcements, &self.config.director)
+    }
+
+    /// Returns the kills needed to finish the current level
+    pub fn get_kill_target(&self) -> u32 {
+        self.level_def.kill_target
+    }
+
+    /// Returns the amount of kills in the current level
+    pub fn get_kills(&self) -> u32 {
+        self.current_kills
+    }
+
+    /// Returns whether the game is over
+    pub fn get_game_over(&self) -> bool {
+        self.game_over
+    }
+
+    /// Returns what happened during the last step
+    pub fn get_events(&self) -> &[Event] {
+        &self.events
+    }
+
+    /// Returns the amount of steps taken since the simulation was created
+    pub fn get_tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Returns the seconds the current run has been played for
+    pub fn get_run_time(&self) -> f64 {
+        self.stats.get_time()
+    }
+
+    /// Returns how the current run went so far
+    pub fn get_stats(&self) -> &RunStats {
+        &self.stats
+    }
+
+    /// Returns the director, which adjusts the game to how the player is
+    /// doing
+    pub fn get_director(&self) -> &Director {
+        &self.director
+    }
+
+    /// Returns the seed the current run started with
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the gameplay values
+    pub fn get_config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Returns the difficulty the config was scaled to
+    pub fn get_difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// Records the difficulty the config was scaled to. The config is left
+    /// alone; give `set_config` the scaled one
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+
+    /// Returns the levels of the game
+    pub fn get_script(&self) -> &Script {
+        &self.script
+    }
+
+    /// Replaces the levels of the game. The current level goes on with the
+    /// kill target and spawns the new script gives it
+    pub fn set_script(&mut self, script: Script) {
+        self.level_def = script.get_level(self.level);
+        self.spawned.resize(self.level_def.spawns.len(), 0);
+        self.script = script;
+    }
+
+    /// Replaces the gameplay values. Things already in the game pick up the
+    /// new values on their next update. Health is left alone; only things
+    /// spawned from now on get the new starting health
+    pub fn set_config(&mut self, config: Config) {
+        self.player.apply_config(&config);
+        for enemy in &mut self.enemies {
+            enemy.apply_config(&config);
+        }
+        for boss in &mut self.bosses {
+            boss.apply_config(&config);
+        }
+        self.directed = self.director.apply(&config);
+        self.config = config;
+    }
+}
+
+/// Returns a screen size of at least 1 by 1, so there is always somewhere to
+/// spawn things
+fn screen_size(width: f64, height: f64) -> [f64; 2] {
+    [width.max(1.0), height.max(1.0)]
+}
+
+/// Writes a list of things with a tag and its length
+fn save_list<T: Snapshot>(w: &mut Writer, tag: &str, list: &[T]) {
+    w.tag(tag);
+    w.value(list.len());
+    for item in list {
+        item.save(w);
+    }
+}
+
+/// Writes a pool with a tag
+fn save_pool<T: Snapshot>(w: &mut Writer, tag: &str, pool: &Pool<T>) {
+    w.tag(tag);
+    pool.save(w);
+}
+
+/// Reads a pool written by `save_pool`
+fn load_pool<T: Snapshot>(r: &mut Reader, tag: &str) -> Result<Pool<T>, SnapshotError> {
+    r.tag(tag)?;
+    Pool::load(r)
+}
+
+/// Reads a list written by `save_list`
+fn load_list<T: Snapshot>(r: &mut Reader, tag: &str) -> Result<Vec<T>, SnapshotError> {
+    r.tag(tag)?;
+    // the length comes from the file, so it can't be trusted to allocate
+    let len: usize = r.value()?;
+    let mut list = Vec::new();
+    for _ in 0..len {
+        list.push(T::load(r)?);
+    }
+    Ok(list)
+}
+
+/// Loads with the default config scaled to the saved difficulty and the
+/// default script. Use `set_config` and `set_script` to play on with others
+impl Snapshot for Simulation {
+    fn save(&self, w: &mut Writer) {
+        w.tag("game");
+        w.value(self.dimensions[0]);
+        w.value(self.dimensions[1]);
+        w.value(self.score);
+        w.value(self.level);
+        w.value(self.current_kills);
+        w.value(self.game_over);
+        w.value(self.seed);
+        for part in &self.rng.get_state() {
+            w.value(part);
+        }
+        w.value(self.tick);
+        w.value(self.level_time);
+        w.tag("difficulty");
+        w.value(self.difficulty.get_name());
+        w.tag("spawned");
+        w.value(self.spawned.len());
+        for count in &self.spawned {
+            w.value(count);
+        }
+        self.stats.save(w);
+        self.director.save(w);
+        self.player.save(w);
+        save_pool(w, "enemies", &self.enemies);
+        save_list(w, "bosses", &self.bosses);
+        save_pool(w, "player_bullets", &self.player_bullets);
+        save_pool(w, "enemy_bullets", &self.enemy_bullets);
+    }
+
+    fn load(r: &mut Reader) -> Result<Self, SnapshotError> {
+        r.tag("game")?;
+        let dimensions: [f64; 2] = [r.value()?, r.value()?];
+        if dimensions[0] < 1.0 || dimensions[1] < 1.0 {
+            return Err(SnapshotError::Malformed(format!("invalid screen size: {} {}",
+                                                        dimensions[0],
+                                                        dimensions[1])));
+        }
+        let score = r.value()?;
+        let level = r.value()?;
+        let current_kills = r.value()?;
+        let game_over = r.value()?;
+        let seed = r.value()?;
+        let rng = GameRng::from_state([r.value()?, r.value()?, r.value()?, r.value()?]);
+        let tick = r.value()?;
+        let level_time = r.value()?;
+        r.tag("difficulty")?;
+        let name: String = r.value()?;
+        let difficulty = Difficulty::parse(&name)
+            .ok_or_else(|| SnapshotError::Malformed(format!("unknown difficulty: {}", name)))?;
+        r.tag("spawned")?;
+        let len: usize = r.value()?;
+        // the length comes from the file, so it can't be trusted to allocate
+        let mut spawned = Vec::new();
+        for _ in 0..len {
+            spawned.push(r.value()?);
+        }
+
+        // the game goes on with the default script until it is given its own,
+        // which needs a count for every spawn of the level
+        let script = Script::default();
+        let level_def = script.get_level(level);
+        spawned.resize(level_def.spawns.len(), 0);
+        let stats = RunStats::load(r)?;
+        let director = Director::load(r)?;
+        let config = difficulty.apply(&Config::default());
+        Ok(Simulation {
+            dimensions,
+            score,
+            level,
+            current_kills,
+            game_over,
+            seed,
+            rng,
+            tick,
+            level_def,
+            script,
+            level_time,
+            spawned,
+            stats,
+            directed: director.apply(&config),
+            director,
+            player: Player::load(r)?,
+            enemies: load_pool(r, "enemies")?,
+            bosses: load_list(r, "bosses")?,
+            player_bullets: load_pool(r, "player_bullets")?,
+            enemy_bullets: load_pool(r, "enemy_bullets")?,
+            config,
+            difficulty,
+            grid: Grid::new(GRIDCELL, &dimensions),
+            candidates: Vec::new(),
+            events: Vec::new(),
+        })
+    }
+}
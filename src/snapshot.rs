@@ -0,0 +1,159 @@
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::str::{FromStr, SplitWhitespace};
+
+/// Version of the snapshot format. Bump it whenever the saved state changes
+pub const VERSION: u32 = 1;
+
+/// Name at the start of every snapshot file
+const HEADER: &str = "hit_n_run-snapshot";
+
+/// Something whose whole state can be saved and restored
+pub trait Snapshot: Sized {
+    /// Writes the state
+    fn save(&self, w: &mut Writer);
+
+    /// Reads back a state written by `save`
+    fn load(r: &mut Reader) -> Result<Self, SnapshotError>;
+}
+
+/// Error returned when a snapshot can't be loaded
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// The file was written by a different version of the game
+    Version(u32),
+    /// The file doesn't contain what was expected
+    Malformed(String),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SnapshotError::Io(ref e) => write!(f, "could not read snapshot: {}", e),
+            SnapshotError::Version(v) => {
+                write!(f, "snapshot has version {}, expected {}", v, VERSION)
+            }
+            SnapshotError::Malformed(ref message) => write!(f, "malformed snapshot: {}", message),
+        }
+    }
+}
+
+impl From<io::Error> for SnapshotError {
+    fn from(e: io::Error) -> Self {
+        SnapshotError::Io(e)
+    }
+}
+
+/// Writes values as text. Every value is tagged with a name so the file can
+/// be read and edited by hand.
+pub struct Writer {
+    /// Text written so far
+    out: String,
+}
+
+impl Writer {
+    /// Returns an empty writer
+    pub fn new() -> Self {
+        Writer { out: String::new() }
+    }
+
+    /// Starts a new line with a tag
+    pub fn tag(&mut self, tag: &str) {
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out.push_str(tag);
+    }
+
+    /// Writes a value. f64s are printed in their shortest form that reads
+    /// back to the exact same value
+    pub fn value<T: fmt::Display>(&mut self, value: T) {
+        self.out.push(' ');
+        self.out.push_str(&value.to_string());
+    }
+
+    /// Returns everything written
+    pub fn finish(mut self) -> String {
+        self.out.push('\n');
+        self.out
+    }
+}
+
+impl Default for Writer {
+    fn default() -> Self {
+        Writer::new()
+    }
+}
+
+/// Reads values written by a `Writer`
+pub struct Reader<'a> {
+    /// Words that have not been read yet
+    words: SplitWhitespace<'a>,
+}
+
+impl<'a> Reader<'a> {
+    /// Returns a reader for `text`
+    pub fn new(text: &'a str) -> Self {
+        Reader { words: text.split_whitespace() }
+    }
+
+    /// Returns the next word
+    fn next(&mut self) -> Result<&'a str, SnapshotError> {
+        self.words
+            .next()
+            .ok_or_else(|| SnapshotError::Malformed("unexpected end of file".to_string()))
+    }
+
+    /// Reads a tag, failing if it isn't `tag`
+    pub fn tag(&mut self, tag: &str) -> Result<(), SnapshotError> {
+        let word = self.next()?;
+        if word == tag {
+            Ok(())
+        } else {
+            Err(SnapshotError::Malformed(format!("expected {}, found {}", tag, word)))
+        }
+    }
+
+    /// Reads a value. Numbers have to be finite
+    pub fn value<T: FromStr>(&mut self) -> Result<T, SnapshotError> {
+        let word = self.next()?;
+        let invalid = || SnapshotError::Malformed(format!("invalid value: {}", word));
+        // f64s read `inf`, `NaN` and numbers too big for them, which no game
+        // is left with
+        if let Ok(f) = word.parse::<f64>() {
+            if !f.is_finite() {
+                return Err(invalid());
+            }
+        }
+        word.parse().map_err(|_| invalid())
+    }
+}
+
+/// Writes a snapshot of `value` to a file
+pub fn save<T: Snapshot, P: AsRef<Path>>(value: &T, path: P) -> io::Result<()> {
+    let mut w = Writer::new();
+    w.tag(HEADER);
+    w.value(VERSION);
+    value.save(&mut w);
+
+    File::create(path)?.write_all(w.finish().as_bytes())
+}
+
+/// Reads a snapshot from a file
+pub fn load<T: Snapshot, P: AsRef<Path>>(path: P) -> Result<T, SnapshotError> {
+    let mut text = String::new();
+    File::open(path)?.read_to_string(&mut text)?;
+
+    let mut r = Reader::new(&text);
+    r.tag(HEADER)?;
+    let version = r.value()?;
+    if version != VERSION {
+        return Err(SnapshotError::Version(version));
+    }
+    T::load(&mut r)
+}
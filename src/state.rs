@@ -0,0 +1,70 @@
+//! Screens the game goes through, from the title to game over. Only one of
+//! them lets the simulation run.
+
+use constants::game_constants::LEVELPAUSE;
+use events::Event;
+
+/// What the game is doing
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum State {
+    /// Waiting for the player to start the first game
+    Title,
+    /// The simulation runs
+    Playing,
+    /// Everything is frozen until the game is resumed
+    Paused,
+    /// A banner announces `level` for `time` more seconds before it starts
+    LevelTransition { level: u32, time: f64 },
+    /// The player died with `score` on `level`
+    GameOver { score: u32, level: u32 },
+}
+
+impl State {
+    /// Returns the banner shown before `level` starts
+    pub fn transition(level: u32) -> Self {
+        State::LevelTransition {
+            level,
+            time: LEVELPAUSE,
+        }
+    }
+
+    /// Returns whether the simulation is stepped. Entities and cooldowns are
+    /// frozen in every other state
+    pub fn is_running(&self) -> bool {
+        *self == State::Playing
+    }
+
+    /// Returns the state once `dt` more seconds have passed
+    pub fn update(self, dt: f64) -> Self {
+        match self {
+            State::LevelTransition { level, time } if time > dt => {
+                State::LevelTransition {
+                    level,
+                    time: time - dt,
+                }
+            }
+            State::LevelTransition { .. } => State::Playing,
+            state => state,
+        }
+    }
+
+    /// Returns the state after the simulation told about `event`
+    pub fn on_event(self, event: &Event) -> Self {
+        match *event {
+            Event::LevelAdvanced { level } => State::transition(level),
+            Event::GameOver { score, level } => State::GameOver { score, level },
+            Event::GameReset { .. } => State::Playing,
+            _ => self,
+        }
+    }
+
+    /// Returns the state after the pause button was pressed. Only a running
+    /// game can be paused
+    pub fn toggle_pause(self) -> Self {
+        match self {
+            State::Playing => State::Paused,
+            State::Paused => State::Playing,
+            state => state,
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Numbers about how the current run is going, kept by the simulation.
+
+use config::Config;
+use events::{DamageSource, Event};
+use snapshot::{Reader, Snapshot, SnapshotError, Writer};
+
+/// How the current run went so far
+#[derive(Clone, Debug, PartialEq)]
+pub struct RunStats {
+    /// Bullets shot by the player
+    pub shots: u32,
+    /// Bullets of the player that hit an enemy or a boss
+    pub hits: u32,
+    /// Health lost to bullets
+    pub bullet_damage: u32,
+    /// Health lost to orbs
+    pub orb_damage: u32,
+    pub enemies_killed: u32,
+    pub bosses_killed: u32,
+    /// Health the player got back for kills
+    pub health_gained: u32,
+    /// Seconds the player waited for a burst to cool down
+    pub burst_cooldown_time: f64,
+    /// Seconds spent on each level, the current one last
+    pub level_times: Vec<f64>,
+    /// Times the director changed its rating
+    pub director_adjustments: u32,
+    /// Lowest rating the director gave
+    pub lowest_rating: f64,
+    /// Highest rating the director gave
+    pub highest_rating: f64,
+}
+
+impl Default for RunStats {
+    fn default() -> Self {
+        RunStats::new()
+    }
+}
+
+impl RunStats {
+    /// Returns the stats of a run that just started on the first level
+    pub fn new() -> Self {
+        RunStats {
+            shots: 0,
+            hits: 0,
+            bullet_damage: 0,
+            orb_damage: 0,
+            enemies_killed: 0,
+            bosses_killed: 0,
+            health_gained: 0,
+            burst_cooldown_time: 0.0,
+            level_times: vec![0.0],
+            director_adjustments: 0,
+            lowest_rating: 0.0,
+            highest_rating: 0.0,
+        }
+    }
+
+    /// Counts an event of the simulation
+    pub fn record(&mut self, event: &Event, config: &Config) {
+        match *event {
+            Event::PlayerFired => self.shots += 1,
+            Event::EnemyHit { .. } | Event::BossHit { .. } => self.hits += 1,
+            Event::EnemyKilled { .. } => {
+                self.hits += 1;
+                self.enemies_killed += 1;
+                self.health_gained += config.game.enemy_kill;
+            }
+            Event::BossKilled { .. } => {
+                self.hits += 1;
+                self.bosses_killed += 1;
+                self.health_gained += config.game.boss_kill;
+            }
+            Event::PlayerDamaged { source: DamageSource::Bullet } => self.bullet_damage += 1,
+            Event::PlayerDamaged { source: DamageSource::Orb } => self.orb_damage += 1,
+            Event::LevelAdvanced { .. } => self.level_times.push(0.0),
+            Event::DirectorAdjusted { rating } => {
+                self.director_adjustments += 1;
+                self.lowest_rating = self.lowest_rating.min(rating);
+                self.highest_rating = self.highest_rating.max(rating);
+            }
+            _ => {}
+        }
+    }
+
+    /// Counts `dt` seconds of play on the current level
+    ///
+    /// # Arguments
+    ///
+    /// * `dt` - Seconds that were played
+    /// * `cooling` - Whether the player was waiting for a burst to cool down
+    pub fn add_time(&mut self, dt: f64, cooling: bool) {
+        if let Some(time) = self.level_times.last_mut() {
+            *time += dt;
+        }
+        if cooling {
+            self.burst_cooldown_time += dt;
+        }
+    }
+
+    /// Returns the fraction of shots that hit something. Zero without shots
+    pub fn get_accuracy(&self) -> f64 {
+        if self.shots == 0 {
+            0.0
+        } else {
+            (self.hits as f64 / self.shots as f64).min(1.0)
+        }
+    }
+
+    /// Returns the seconds the run has been played for
+    pub fn get_time(&self) -> f64 {
+        self.level_times.iter().sum()
+    }
+}
+
+impl Snapshot for RunStats {
+    fn save(&self, w: &mut Writer) {
+        w.tag("stats");
+        w.value(self.shots);
+        w.value(self.hits);
+        w.value(self.bullet_damage);
+        w.value(self.orb_damage);
+        w.value(self.enemies_killed);
+        w.value(self.bosses_killed);
+        w.value(self.health_gained);
+        w.value(self.burst_cooldown_time);
+        w.value(self.level_times.len());
+        for time in &self.level_times {
+            w.value(time);
+        }
+        w.value(self.director_adjustments);
+        w.value(self.lowest_rating);
+        w.value(self.highest_rating);
+    }
+
+    fn load(r: &mut Reader) -> Result<Self, SnapshotError> {
+        r.tag("stats")?;
+        let mut stats = RunStats {
+            shots: r.value()?,
+            hits: r.value()?,
+            bullet_damage: r.value()?,
+            orb_damage: r.value()?,
+            enemies_killed: r.value()?,
+            bosses_killed: r.value()?,
+            health_gained: r.value()?,
+            burst_cooldown_time: r.value()?,
+            level_times: Vec::new(),
+            director_adjustments: 0,
+            lowest_rating: 0.0,
+            highest_rating: 0.0,
+        };
+        let len: usize = r.value()?;
+        for _ in 0..len {
+            stats.level_times.push(r.value()?);
+        }
+        stats.director_adjustments = r.value()?;
+        stats.lowest_rating = r.value()?;
+        stats.highest_rating = r.value()?;
+        Ok(stats)
+    }
+}
@@ -1,4 +1,9 @@
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use snapshot::{Reader, Snapshot, SnapshotError, Writer};
+
 /// Vector
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Vector {
     /// X component
     pub x: f64,
@@ -9,7 +14,13 @@ pub struct Vector {
 impl Vector {
     /// Returns a vector
     pub fn new(x: f64, y: f64) -> Self {
-        Vector { x: x, y: y }
+        Vector { x, y }
+    }
+
+    /// Returns the unit vector pointing `angle` radians from the x axis
+    pub fn from_angle(angle: f64) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Vector::new(cos, sin)
     }
 
     /// Resest the x and y components to 0
@@ -18,10 +29,143 @@ impl Vector {
         self.y = 0.0;
     }
 
+    /// Returns the length of the vector
+    pub fn length(&self) -> f64 {
+        self.x.hypot(self.y)
+    }
+
+    /// Returns the length of the vector squared. Cheaper than `length`
+    pub fn length_squared(&self) -> f64 {
+        self.dot(self)
+    }
+
+    /// Returns the vector with a length of 1. The zero vector stays zero
+    pub fn normalize(&self) -> Vector {
+        let length = self.length();
+        if length == 0.0 {
+            *self
+        } else {
+            *self / length
+        }
+    }
+
+    /// Returns the dot product
+    pub fn dot(&self, other: &Vector) -> f64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    /// Returns the z component of the cross product. Positive when `other`
+    /// is counterclockwise from this vector
+    pub fn cross(&self, other: &Vector) -> f64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    /// Returns the vector turned `angle` radians counterclockwise
+    pub fn rotate(&self, angle: f64) -> Vector {
+        let (sin, cos) = angle.sin_cos();
+        Vector::new(self.x * cos - self.y * sin, self.x * sin + self.y * cos)
+    }
+
+    /// Returns the angle from the x axis in radians, between -pi and pi
+    pub fn angle(&self) -> f64 {
+        self.y.atan2(self.x)
+    }
+
+    /// Returns the vector `t` of the way from this vector to `other`
+    pub fn lerp(&self, other: &Vector, t: f64) -> Vector {
+        *self + (*other - *self) * t
+    }
+
     /// Returns the distance between two vector
     pub fn dist(&self, other: &Vector) -> f64 {
-        let xdiff = self.x - other.x;
-        let ydiff = self.y - other.y;
-        (xdiff.powi(2) + ydiff.powi(2)).sqrt()
+        (*self - *other).length()
+    }
+
+    /// Returns the closest point inside the rectangle from `min` to `max`
+    pub fn clamp_to_rect(&self, min: &Vector, max: &Vector) -> Vector {
+        Vector::new(self.x.max(min.x).min(max.x), self.y.max(min.y).min(max.y))
+    }
+}
+
+impl Add for Vector {
+    type Output = Vector;
+
+    fn add(self, other: Vector) -> Vector {
+        Vector::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Vector {
+    type Output = Vector;
+
+    fn sub(self, other: Vector) -> Vector {
+        Vector::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Vector {
+    type Output = Vector;
+
+    fn mul(self, scale: f64) -> Vector {
+        Vector::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl Mul<Vector> for f64 {
+    type Output = Vector;
+
+    fn mul(self, v: Vector) -> Vector {
+        v * self
+    }
+}
+
+impl Div<f64> for Vector {
+    type Output = Vector;
+
+    fn div(self, scale: f64) -> Vector {
+        Vector::new(self.x / scale, self.y / scale)
+    }
+}
+
+impl Neg for Vector {
+    type Output = Vector;
+
+    fn neg(self) -> Vector {
+        Vector::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Vector {
+    fn add_assign(&mut self, other: Vector) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Vector {
+    fn sub_assign(&mut self, other: Vector) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign<f64> for Vector {
+    fn mul_assign(&mut self, scale: f64) {
+        *self = *self * scale;
+    }
+}
+
+impl DivAssign<f64> for Vector {
+    fn div_assign(&mut self, scale: f64) {
+        *self = *self / scale;
+    }
+}
+
+impl Snapshot for Vector {
+    fn save(&self, w: &mut Writer) {
+        w.value(self.x);
+        w.value(self.y);
+    }
+
+    fn load(r: &mut Reader) -> Result<Self, SnapshotError> {
+        Ok(Vector::new(r.value()?, r.value()?))
     }
 }
@@ -1,21 +1,18 @@
-extern crate piston;
-extern crate graphics;
-extern crate glutin_window;
-extern crate opengl_graphics;
-
-use opengl_graphics::GlGraphics;
-use piston::input::*;
 use std::f64;
 use vector::Vector;
-use constants::bullet_constants::*;
-use constants::color::*;
+use collision::Collider;
+use collision::shape::Shape;
+use config::Config;
+use snapshot::{Reader, Snapshot, SnapshotError, Writer};
 
 /// Bullets
 pub struct Bullet {
     /// Position of the bullet
     pos: Vector,
-    /// Velocity of the bullet
-    vel: Vector,
+    /// Position before the last update
+    prev_pos: Vector,
+    /// Direction the bullet is moving in, as a unit vector
+    dir: Vector,
     /// Rotation of the bullet
     rotation: f64,
     /// Whether the bullet is alive
@@ -29,7 +26,8 @@ impl Bullet {
     pub fn new(xpos: f64, ypos: f64, rot: f64, b: bool) -> Self {
         Bullet {
             pos: Vector::new(xpos, ypos),
-            vel: Vector::new(VEL * rot.cos(), VEL * rot.sin()),
+            prev_pos: Vector::new(xpos, ypos),
+            dir: Vector::from_angle(rot),
             rotation: rot,
             alive: true,
             friendly: b,
@@ -37,7 +35,7 @@ impl Bullet {
     }
 
     /// Friendly bullets die once they hit the end of the screen
-    fn friendly_mov(&mut self, args: &UpdateArgs, width: f64, height: f64) {
+    fn friendly_mov(&mut self, dt: f64, width: f64, height: f64, vel: f64) {
         // check x position
         if self.pos.x < 0.0 || self.pos.x > width || self.pos.y < 0.0 || self.pos.y > height {
             self.set_alive(false);
@@ -45,55 +43,37 @@ impl Bullet {
         }
 
         // update x and y
-        self.pos.x += self.vel.x * VEL * args.dt;
-        self.pos.y += self.vel.y * VEL * args.dt;
+        self.pos += self.dir * (vel * dt);
     }
 
     /// Enemy bullets bounce when they hit the end of the screen
-    fn enemy_mov(&mut self, args: &UpdateArgs, width: f64, height: f64) {
+    fn enemy_mov(&mut self, dt: f64, width: f64, height: f64, vel: f64) {
         // make sure bullet doesnt leave the screen
         // check x position
         if self.pos.x < 0.0 || self.pos.x > width {
-            self.vel.x *= -1.0;
+            self.dir.x *= -1.0;
         }
 
         // check y position
         if self.pos.y < 0.0 || self.pos.y > height {
-            self.vel.y *= -1.0;
+            self.dir.y *= -1.0;
         }
 
         // update x and y
-        self.pos.x += self.vel.x * VEL * args.dt;
-        self.pos.y += self.vel.y * VEL * args.dt;
-    }
-
-    /// Draws the bullet
-    pub fn draw(&self, c: graphics::Context, gl: &mut GlGraphics) {
-        use graphics::*;
-        let square = rectangle::square(0.0, 0.0, SIDELENGTH);
-
-        // get color of bullet
-        let color = if self.get_friendly() { LIGHTBLUE } else { RED };
-
-        // create transform matrix
-        let transform = c.transform
-            .trans(self.pos.x, self.pos.y)
-            .rot_rad(self.rotation)
-            .trans(-SIDELENGTH / 2.0, -SIDELENGTH / 2.0);
-
-        // Draw a box rotating around the middle of the screen.
-        rectangle(color, square, transform, gl);
+        self.pos += self.dir * (vel * dt);
     }
 
     /// Updates the bullet position and rotation
-    pub fn update(&mut self, args: &UpdateArgs, dimensions: &[f64; 2]) {
+    pub fn update(&mut self, dt: f64, dimensions: &[f64; 2], config: &Config) {
+        self.prev_pos = self.pos;
+        let vel = config.bullet.velocity;
         if self.get_friendly() {
-            self.friendly_mov(args, dimensions[0], dimensions[1]);
+            self.friendly_mov(dt, dimensions[0], dimensions[1], vel);
         } else {
-            self.enemy_mov(args, dimensions[0], dimensions[1]);
+            self.enemy_mov(dt, dimensions[0], dimensions[1], vel);
         }
 
-        self.rotation = self.vel.y.atan2(self.vel.x);
+        self.rotation = self.dir.angle();
     }
 
     /// Returns whether the bullet is alive
@@ -116,8 +96,66 @@ impl Bullet {
         self.pos.y
     }
 
+    /// Returns the bullet's position
+    pub fn get_pos(&self) -> Vector {
+        self.pos
+    }
+
+    /// Returns the bullet's position before the last update
+    pub fn get_prev_pos(&self) -> Vector {
+        self.prev_pos
+    }
+
+    /// Returns the bullet's rotation
+    pub fn get_rotation(&self) -> f64 {
+        self.rotation
+    }
+
     /// Returns whether the bullet is friendly
-    fn get_friendly(&self) -> bool {
+    pub fn get_friendly(&self) -> bool {
         self.friendly
     }
 }
+
+/// Bullets collide as the turned square they are drawn as
+impl Collider for Bullet {
+    fn get_shape(&self, config: &Config) -> Shape {
+        let half = config.bullet.side_length / 2.0;
+        Shape::Obb {
+            center: self.pos,
+            half_size: Vector::new(half, half),
+            rotation: self.rotation,
+        }
+    }
+
+    /// Bullets always move the way they face, so the square stretched
+    /// between the last two positions is everything it went through
+    fn get_swept_shape(&self, config: &Config) -> Shape {
+        let half = config.bullet.side_length / 2.0;
+        Shape::swept_box(self.prev_pos, self.pos, Vector::new(half, half), self.rotation)
+    }
+}
+
+impl Snapshot for Bullet {
+    fn save(&self, w: &mut Writer) {
+        w.tag("bullet");
+        self.pos.save(w);
+        self.prev_pos.save(w);
+        self.dir.save(w);
+        w.value(self.rotation);
+        w.value(self.alive);
+        w.value(self.friendly);
+    }
+
+    fn load(r: &mut Reader) -> Result<Self, SnapshotError> {
+        r.tag("bullet")?;
+        Ok(Bullet {
+            pos: Vector::load(r)?,
+            prev_pos: Vector::load(r)?,
+            dir: Vector::load(r)?,
+            rotation: r.value()?,
+            alive: r.value()?,
+            friendly: r.value()?,
+        })
+    }
+}
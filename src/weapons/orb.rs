@@ -3,7 +3,10 @@ extern crate rand;
 use rand::Rng;
 use vector::Vector;
 use std::f64;
-use constants::orb_constants::*;
+use collision::Collider;
+use collision::shape::Shape;
+use config::Config;
+use snapshot::{Reader, Snapshot, SnapshotError, Writer};
 
 /// Orbs
 pub struct Orb {
@@ -13,6 +16,8 @@ pub struct Orb {
     theta: f64,
     /// The position of the angle
     pos: Vector,
+    /// Position before the last update
+    prev_pos: Vector,
     /// Cooldown of orb activity
     cooldown: f64,
     /// Diameter of the orbit
@@ -21,16 +26,16 @@ pub struct Orb {
 
 impl Orb {
     /// Return a new orb
-    pub fn new(x: f64, y: f64, diameter: f64) -> Self {
+    pub fn new<R: Rng>(x: f64, y: f64, diameter: f64, config: &Config, rng: &mut R) -> Self {
         // get the original position of the orb
-        let rng_angle = rand::thread_rng().gen_range(0.0, 2.0);
-        let posx = x + (diameter * f64::consts::PI * rng_angle).cos();
-        let posy = y + (diameter * f64::consts::PI * rng_angle).sin();
+        let rng_angle = rng.gen_range(0.0, 2.0);
+        let pos = Vector::new(x, y) + Vector::from_angle(diameter * f64::consts::PI * rng_angle);
         Orb {
             active: true,
             theta: rng_angle,
-            pos: Vector::new(posx, posy),
-            cooldown: COOLDOWN,
+            pos,
+            prev_pos: pos,
+            cooldown: config.orb.cooldown,
             orbit_diameter: diameter
         }
     }
@@ -45,23 +50,33 @@ impl Orb {
         self.pos.y
     }
 
+    /// Return the position of the orb
+    pub fn get_pos(&self) -> Vector {
+        self.pos
+    }
+
+    /// Return the position of the orb before the last update
+    pub fn get_prev_pos(&self) -> Vector {
+        self.prev_pos
+    }
+
     /// Update the orbs postition and angle
-    pub fn update(&mut self, x: f64, y: f64, dt: f64) {
-        self.theta += VEL * f64::consts::PI;
+    pub fn update(&mut self, center: Vector, dt: f64, config: &Config) {
+        self.prev_pos = self.pos;
+        self.theta += config.orb.velocity * f64::consts::PI * dt;
 
         // 2pi is the same as 0
-        if (self.theta - 2.0 * f64::consts::PI).abs() < EPSILON {
-            self.theta = 0.0;
+        if self.theta >= 2.0 * f64::consts::PI {
+            self.theta -= 2.0 * f64::consts::PI;
         }
 
         // update position
-        self.pos.x = x + self.orbit_diameter * self.theta.cos();
-        self.pos.y = y + self.orbit_diameter * self.theta.sin();
+        self.pos = center + Vector::from_angle(self.theta) * self.orbit_diameter;
 
         // udpate active cooldown
         if !self.active {
             if self.cooldown < 0.0 {
-                self.cooldown = COOLDOWN;
+                self.cooldown = config.orb.cooldown;
                 self.set_active(true);
             } else {
                 self.cooldown -= dt;
@@ -69,6 +84,13 @@ impl Orb {
         }
     }
 
+    /// Adopts a new config and orbit. Cooldowns longer than the new one are
+    /// cut short
+    pub fn apply_config(&mut self, orbit_diameter: f64, config: &Config) {
+        self.orbit_diameter = orbit_diameter;
+        self.cooldown = self.cooldown.min(config.orb.cooldown);
+    }
+
     /// Set whether the orb is active or not
     pub fn set_active(&mut self, b: bool) {
         self.active = b;
@@ -79,3 +101,49 @@ impl Orb {
         self.active
     }
 }
+
+impl Collider for Orb {
+    fn get_shape(&self, config: &Config) -> Shape {
+        Shape::Circle {
+            center: self.pos,
+            radius: config.enemy.orb_diameter / 2.0,
+        }
+    }
+
+    fn get_swept_shape(&self, config: &Config) -> Shape {
+        Shape::Capsule {
+            start: self.prev_pos,
+            end: self.pos,
+            radius: config.enemy.orb_diameter / 2.0,
+        }
+    }
+}
+
+impl Snapshot for Orb {
+    fn save(&self, w: &mut Writer) {
+        w.tag("orb");
+        w.value(self.active);
+        w.value(self.theta);
+        self.pos.save(w);
+        self.prev_pos.save(w);
+        w.value(self.cooldown);
+        w.value(self.orbit_diameter);
+    }
+
+    fn load(r: &mut Reader) -> Result<Self, SnapshotError> {
+        r.tag("orb")?;
+        let orb = Orb {
+            active: r.value()?,
+            theta: r.value()?,
+            pos: Vector::load(r)?,
+            prev_pos: Vector::load(r)?,
+            cooldown: r.value()?,
+            orbit_diameter: r.value()?,
+        };
+        if orb.orbit_diameter < 0.0 {
+            return Err(SnapshotError::Malformed(format!("invalid orbit diameter: {}",
+                                                        orb.orbit_diameter)));
+        }
+        Ok(orb)
+    }
+}
@@ -0,0 +1,144 @@
+extern crate hit_n_run;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use hit_n_run::achievements::{Achievement, Achievements};
+use hit_n_run::config::Config;
+use hit_n_run::constants::achievement_constants::{SURVIVORTIME, VETERANLEVEL};
+use hit_n_run::constants::game_constants::TIMESTEP;
+use hit_n_run::constants::player_constants::STARTSHOTS;
+use hit_n_run::events::{DamageSource, Event, Observer};
+use hit_n_run::script::Script;
+use hit_n_run::simulation::Simulation;
+use hit_n_run::vector::Vector;
+
+/// Returns a path for an achievements file in the temporary directory
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("hit_n_run-test-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn bytes_that_arent_text_only_cost_their_lines() {
+    let path = temp_path("achievements-binary");
+    fs::write(&path,
+              &b"hit_n_run achievements 1\n\
+                 veteran 2017-05-04\n\
+                 flaw\xfeless 2017-05-05\n\
+                 survivor 2017-\xff05-06\n\
+                 marksman 2017-05-07\n"[..])
+        .unwrap();
+
+    let (achievements, skipped) = Achievements::load(&path).unwrap();
+    assert_eq!(skipped, 1);
+    assert_eq!(achievements.get_date(Achievement::Veteran), Some("2017-05-04"));
+    assert!(!achievements.get_unlocked(Achievement::Flawless));
+    assert_eq!(achievements.get_date(Achievement::Survivor), Some("2017-\u{fffd}05-06"));
+    assert_eq!(achievements.get_date(Achievement::Marksman), Some("2017-05-07"));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn missing_file_unlocks_nothing() {
+    let (achievements, skipped) = Achievements::load(temp_path("achievements-missing")).unwrap();
+    assert!(Achievement::ALL.iter().all(|&a| !achievements.get_unlocked(a)));
+    assert_eq!(skipped, 0);
+}
+
+/// Returns the achievements unlocked by feeding `events` to a fresh set
+fn unlocked_by(events: &[Event]) -> Vec<Achievement> {
+    let mut achievements = Achievements::new();
+    feed(&mut achievements, events);
+    achievements.take_new()
+}
+
+/// Feeds events to the achievements, one tick apart
+fn feed(achievements: &mut Achievements, events: &[Event]) {
+    for (tick, event) in events.iter().enumerate() {
+        achievements.on_event(tick as u64, event);
+    }
+}
+
+fn kill() -> Event {
+    Event::EnemyKilled { pos: Vector::new(0.0, 0.0) }
+}
+
+fn boss_kill() -> Event {
+    Event::BossKilled { pos: Vector::new(0.0, 0.0) }
+}
+
+fn damage() -> Event {
+    Event::PlayerDamaged { source: DamageSource::Bullet }
+}
+
+#[test]
+fn veteran_takes_reaching_its_level() {
+    let advance = |level| Event::LevelAdvanced { level };
+    assert!(!unlocked_by(&[advance(VETERANLEVEL - 1)]).contains(&Achievement::Veteran));
+    assert!(unlocked_by(&[advance(VETERANLEVEL)]).contains(&Achievement::Veteran));
+}
+
+#[test]
+fn flawless_takes_a_boss_without_damage_on_its_level() {
+    let advance = Event::LevelAdvanced { level: 2 };
+    assert_eq!(unlocked_by(&[damage(), advance, boss_kill()]), vec![Achievement::Flawless]);
+    assert!(unlocked_by(&[damage(), boss_kill()]).is_empty());
+    assert!(unlocked_by(&[kill()]).is_empty());
+
+    // damage is forgotten when the game is reset
+    let reset = Event::GameReset { seed: 1 };
+    assert_eq!(unlocked_by(&[damage(), reset, boss_kill()]), vec![Achievement::Flawless]);
+}
+
+#[test]
+fn marksman_takes_a_level_of_several_kills_in_one_burst() {
+    let advance = Event::LevelAdvanced { level: 2 };
+    let mut burst = vec![Event::PlayerFired; STARTSHOTS as usize];
+
+    // a level of a single kill is too easy
+    burst.push(kill());
+    burst.push(advance);
+    assert!(unlocked_by(&burst).is_empty());
+
+    burst.insert(0, kill());
+    assert_eq!(unlocked_by(&burst), vec![Achievement::Marksman]);
+
+    // one shot too many
+    burst.insert(0, Event::PlayerFired);
+    assert!(unlocked_by(&burst).is_empty());
+}
+
+#[test]
+fn loaded_level_doesnt_count() {
+    let mut achievements = Achievements::new();
+    feed(&mut achievements, &[damage()]);
+    achievements.on_load();
+    feed(&mut achievements, &[kill(), boss_kill(), Event::LevelAdvanced { level: 2 }]);
+    assert!(achievements.take_new().is_empty());
+
+    // the next level is played from its start
+    feed(&mut achievements, &[kill(), boss_kill()]);
+    assert_eq!(achievements.take_new(), vec![Achievement::Flawless]);
+}
+
+#[test]
+fn survivor_takes_staying_alive() {
+    // a player that can't die standing still
+    let mut config = Config::default();
+    config.player.start_health = 1_000_000;
+    let mut sim = Simulation::new(800.0, 600.0, 1, config, Script::default());
+    let mut achievements = Achievements::new();
+
+    let steps = (SURVIVORTIME / TIMESTEP).ceil() as u64;
+    for _ in 0..steps - 10 {
+        sim.step(TIMESTEP, &[]);
+        achievements.on_step(&sim);
+    }
+    assert!(achievements.take_new().is_empty());
+    for _ in 0..20 {
+        sim.step(TIMESTEP, &[]);
+        achievements.on_step(&sim);
+    }
+    assert_eq!(achievements.take_new(), vec![Achievement::Survivor]);
+}
@@ -0,0 +1,148 @@
+extern crate hit_n_run;
+
+use hit_n_run::config::Config;
+use hit_n_run::script::{Area, Kind, Script};
+use hit_n_run::settings::{parse_button, Action, Movement, Settings};
+
+/// Returns the message of the error a config gives
+fn config_error(text: &str) -> String {
+    Config::parse(text).expect_err("config should not parse").to_string()
+}
+
+/// Returns the message of the error a script gives
+fn script_error(text: &str) -> String {
+    Script::parse(text).expect_err("levels should not parse").to_string()
+}
+
+/// Returns the message of the error settings give
+fn settings_error(text: &str) -> String {
+    Settings::parse(text).expect_err("settings should not parse").to_string()
+}
+
+#[test]
+fn config_keeps_defaults_for_keys_left_out() {
+    assert_eq!(Config::parse("").unwrap(), Config::default());
+
+    let config = Config::parse("[player]\nvelocity = 300\n[director]\nenabled = true").unwrap();
+    assert_eq!(config.player.velocity, 300.0);
+    assert!(config.director.enabled);
+    assert_eq!(config.enemy, Config::default().enemy);
+}
+
+#[test]
+fn config_errors_name_the_key() {
+    assert_eq!(config_error("[player]\nvelocity = -1"),
+               "config line 2: player.velocity: must not be negative");
+    assert_eq!(config_error("[player]\ndiameter = 0"),
+               "config line 2: player.diameter: must be greater than 0");
+    assert_eq!(config_error("[player]\nstart_health = 0"),
+               "config line 2: player.start_health: must be at least 1");
+    assert_eq!(config_error("[player]\nstart_health = 1.5"),
+               "config line 2: player.start_health: expected a whole number");
+    assert_eq!(config_error("[enemy]\nstart_health = 5000000000"),
+               "config line 2: enemy.start_health: must be a whole number from 0 to 4294967295");
+    assert_eq!(config_error("[director]\nenabled = 1"),
+               "config line 2: director.enabled: expected true or false");
+    assert_eq!(config_error("[enemy]\n\ncooldown = \"fast\""),
+               "config line 3: enemy.cooldown: expected a number");
+}
+
+#[test]
+fn config_refuses_unknown_names() {
+    assert_eq!(config_error("[player]\nspeed = 1"), "config line 2: unknown key player.speed");
+    assert_eq!(config_error("speed = 1"), "config line 1: unknown key speed");
+    assert_eq!(config_error("[wizard]"), "config line 1: unknown table [wizard]");
+    assert_eq!(config_error("[[player]]"), "config line 1: unknown table [player]");
+}
+
+#[test]
+fn config_syntax_errors_name_the_line() {
+    assert_eq!(config_error("[player]\nvelocity = inf"), "config line 2: invalid value: inf");
+    assert_eq!(config_error("[player]\nvelocity = 1\nvelocity = 2"),
+               "config line 3: key velocity is defined twice");
+}
+
+#[test]
+fn script_reads_levels_and_spawns() {
+    let script = Script::parse("[[level]]\n\
+                                kill_target = 3\n\
+                                [[level.spawn]]\n\
+                                kind = \"boss\"\n\
+                                at = [0.5, 0.25]\n\
+                                [[level]]\n\
+                                kill_target = 5\n\
+                                [endless]\n\
+                                enemies = 2")
+        .unwrap();
+    assert_eq!(script.levels.len(), 2);
+    assert_eq!(script.levels[0].kill_target, 3);
+    assert_eq!(script.levels[0].spawns[0].kind, Kind::Boss);
+    assert_eq!(script.levels[0].spawns[0].area, Area::Point { x: 0.5, y: 0.25 });
+    assert!(script.levels[1].spawns.is_empty());
+    assert_eq!(script.endless.enemies, 2);
+}
+
+#[test]
+fn script_errors_name_the_key() {
+    assert_eq!(script_error("[[level]]\nkill_target = 0"),
+               "levels line 2: level.kill_target: must be at least 1");
+    assert_eq!(script_error("[[level]]\nclear_enemies = true"),
+               "levels line 1: missing key level.kill_target");
+    assert_eq!(script_error("[[level]]\nkill_target = 1\n[[level.spawn]]\nkind = \"dragon\""),
+               "levels line 4: level.spawn.kind: unknown kind: dragon");
+    assert_eq!(script_error("[[level]]\nkill_target = 1\n[[level.spawn]]\ndelay = -2"),
+               "levels line 4: level.spawn.delay: must not be negative");
+    assert_eq!(script_error("[[level]]\nkill_target = 1\n[[level.spawn]]\nat = [0.5, 1.5]"),
+               "levels line 4: level.spawn.at: expected an array of 2 numbers from 0 to 1");
+    assert_eq!(script_error("[[level]]\nkill_target = 1\n[[level.spawn]]\n\
+                             region = [0.5, 0, 0.6, 1]"),
+               "levels line 4: level.spawn.region: must be inside the screen");
+    assert_eq!(script_error("[[level]]\nkill_target = 1\n[[level.spawn]]\n\
+                             at = [0, 0]\nregion = [0, 0, 1, 1]"),
+               "levels line 5: level.spawn.region: only one of region and at can be given");
+    assert_eq!(script_error("[endless]\nbosses = -1"),
+               "levels line 2: endless.bosses: must be a whole number from 0 to 4294967295");
+}
+
+#[test]
+fn script_refuses_bad_structure() {
+    assert_eq!(script_error(""), "levels: there has to be at least one [[level]]");
+    assert_eq!(script_error("[[level.spawn]]\ncount = 1"),
+               "levels line 1: [[level.spawn]] must come after a [[level]]");
+    assert_eq!(script_error("[level]\nkill_target = 1"), "levels line 1: unknown table [level]");
+    assert_eq!(script_error("speed = 1"), "levels line 1: unknown key speed");
+    assert_eq!(script_error("[[level]]\nkill_target = 1\nboss = true"),
+               "levels line 3: unknown key level.boss");
+}
+
+#[test]
+fn settings_read_controls_and_bindings() {
+    let settings = Settings::parse("[controls]\nmovement = \"keyboard\"\n\
+                                    [bindings]\nfire = [\"X\", \"mouseleft\"]")
+        .unwrap();
+    assert_eq!(settings.movement, Movement::Keyboard);
+    assert_eq!(settings.bindings.get_buttons(Action::Fire),
+               vec![parse_button("X").unwrap(), parse_button("MouseLeft").unwrap()]);
+    assert_eq!(settings.bindings.get_action(parse_button("Space").unwrap()), None);
+    assert_eq!(settings.bindings.get_buttons(Action::Reset),
+               Settings::default().bindings.get_buttons(Action::Reset));
+}
+
+#[test]
+fn settings_errors_name_the_key() {
+    assert_eq!(settings_error("[bindings]\nfire = 1"),
+               "settings line 2: bindings.fire: expected a button or an array of buttons");
+    assert_eq!(settings_error("[bindings]\nfire = [\"Space\", 1]"),
+               "settings line 2: bindings.fire: expected a button name");
+    assert_eq!(settings_error("[bindings]\nfire = \"Nope\""),
+               "settings line 2: bindings.fire: unknown button: Nope");
+    assert_eq!(settings_error("[bindings]\nfire = \"X\"\nreset = \"X\""),
+               "settings line 3: bindings.reset: button is already bound to fire");
+    assert_eq!(settings_error("[controls]\nmovement = \"joystick\""),
+               "settings line 2: controls.movement: expected \"mouse\" or \"keyboard\"");
+    assert_eq!(settings_error("[bindings]\njump = \"J\""),
+               "settings line 2: unknown key bindings.jump");
+    assert_eq!(settings_error("[controls]\nspeed = 1"),
+               "settings line 2: unknown key controls.speed");
+    assert_eq!(settings_error("[keys]"), "settings line 1: unknown table [keys]");
+}
@@ -0,0 +1,40 @@
+extern crate hit_n_run;
+
+use hit_n_run::config::Config;
+use hit_n_run::controller::Controller;
+use hit_n_run::controller::mouse::Mouse;
+use hit_n_run::script::Script;
+use hit_n_run::simulation::{Input, Simulation};
+
+#[test]
+fn mouse_keeps_only_the_latest_position() {
+    let sim = Simulation::new(800.0, 600.0, 1, Config::default(), Script::default());
+    let mut mouse = Mouse::new();
+    // what a long pause leaves behind
+    for i in 0..1000 {
+        let f = f64::from(i);
+        mouse.window_input(Input::Target(f, f));
+        mouse.window_input(Input::Aim(f, 0.0));
+        if i == 500 {
+            mouse.window_input(Input::Shoot(true));
+            mouse.window_input(Input::Dash);
+        }
+        mouse.window_input(Input::Move(0.0, 1.0));
+    }
+    mouse.window_input(Input::Shoot(false));
+
+    let mut input = Vec::new();
+    mouse.inputs(&sim, &mut input);
+    assert_eq!(input,
+               vec![Input::Shoot(true),
+                    Input::Dash,
+                    Input::Target(999.0, 999.0),
+                    Input::Aim(999.0, 0.0),
+                    Input::Move(0.0, 1.0),
+                    Input::Shoot(false)]);
+
+    // everything was handed on
+    input.clear();
+    mouse.inputs(&sim, &mut input);
+    assert!(input.is_empty());
+}
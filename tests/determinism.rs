@@ -0,0 +1,106 @@
+extern crate hit_n_run;
+
+use hit_n_run::config::Config;
+use hit_n_run::constants::game_constants::TIMESTEP;
+use hit_n_run::controller::Controller;
+use hit_n_run::controller::bot::{Bot, Strategy};
+use hit_n_run::events::Event;
+use hit_n_run::script::Script;
+use hit_n_run::simulation::{Input, Simulation};
+use hit_n_run::snapshot::{Snapshot, Writer};
+
+/// Returns the whole state of a simulation as snapshot text
+fn state(sim: &Simulation) -> String {
+    let mut w = Writer::new();
+    sim.save(&mut w);
+    w.finish()
+}
+
+/// Returns a simulation at the start of a game
+fn new_sim(seed: u64) -> Simulation {
+    Simulation::new(800.0, 600.0, seed, Config::default(), Script::default())
+}
+
+/// Returns the input given on `tick`: the bot plays, and every now and then
+/// the player dashes, is steered with the keys or the game is reset
+fn inputs(tick: u64, bot: &mut Bot, sim: &Simulation) -> Vec<Input> {
+    let mut input = Vec::new();
+    bot.inputs(sim, &mut input);
+    if tick % 97 == 96 {
+        input.push(Input::Dash);
+    }
+    if tick % 331 < 30 {
+        input.push(Input::Move(0.6, -0.8));
+    }
+    if tick % 2000 == 1999 {
+        input.push(Input::Reset);
+    }
+    input
+}
+
+/// Steps a simulation for `ticks` steps, returning the events of every step
+fn play(sim: &mut Simulation, ticks: u64) -> Vec<(u64, Event)> {
+    let mut bot = Bot::new(Strategy::Hunter);
+    let mut events = Vec::new();
+    for tick in 0..ticks {
+        let input = inputs(tick, &mut bot, sim);
+        sim.step(TIMESTEP, &input);
+        events.extend(sim.get_events().iter().map(|&e| (sim.get_tick(), e)));
+    }
+    events
+}
+
+#[test]
+fn same_seed_and_input_give_same_game() {
+    let (mut a, mut b) = (new_sim(7), new_sim(7));
+    assert_eq!(state(&a), state(&b));
+
+    let events_a = play(&mut a, 6000);
+    let events_b = play(&mut b, 6000);
+    assert_eq!(events_a, events_b);
+    assert_eq!(state(&a), state(&b));
+
+    // the game has to have gone somewhere for this to mean anything
+    assert!(events_a.iter().any(|&(_, e)| matches!(e, Event::EnemyKilled { .. })));
+    assert!(events_a.iter().any(|&(_, e)| matches!(e, Event::GameReset { .. })));
+}
+
+#[test]
+fn same_game_step_by_step() {
+    let (mut a, mut b) = (new_sim(99), new_sim(99));
+    let (mut bot_a, mut bot_b) = (Bot::new(Strategy::Hunter), Bot::new(Strategy::Hunter));
+    for tick in 0..1500 {
+        let input_a = inputs(tick, &mut bot_a, &a);
+        let input_b = inputs(tick, &mut bot_b, &b);
+        assert_eq!(input_a, input_b);
+        a.step(TIMESTEP, &input_a);
+        b.step(TIMESTEP, &input_b);
+        assert_eq!(state(&a), state(&b), "games split on tick {}", tick);
+    }
+}
+
+#[test]
+fn different_seeds_give_different_games() {
+    let (mut a, mut b) = (new_sim(1), new_sim(2));
+    play(&mut a, 600);
+    play(&mut b, 600);
+    assert_ne!(state(&a), state(&b));
+}
+
+#[test]
+fn reset_seed_depends_only_on_the_run() {
+    let (mut a, mut b) = (new_sim(5), new_sim(5));
+    a.step(TIMESTEP, &[Input::Reset]);
+    b.step(TIMESTEP, &[Input::Reset]);
+    assert_eq!(a.get_seed(), b.get_seed());
+    assert_ne!(a.get_seed(), 5);
+
+    // a run started from the seed of a reset plays like a new game with it.
+    // The reset step also played a step of the new run
+    let mut c = new_sim(a.get_seed());
+    c.step(TIMESTEP, &[]);
+    play(&mut a, 300);
+    play(&mut c, 300);
+    assert_eq!(a.get_score(), c.get_score());
+    assert_eq!(a.get_player().get_pos(), c.get_player().get_pos());
+}
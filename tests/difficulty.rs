@@ -0,0 +1,70 @@
+extern crate hit_n_run;
+
+use hit_n_run::config::difficulty::Difficulty;
+use hit_n_run::config::Config;
+use hit_n_run::constants::{boss_constants, enemy_constants, game_constants, orb_constants,
+                           player_constants};
+
+#[test]
+fn normal_leaves_the_config_alone() {
+    let config = Config::default();
+    assert_eq!(Difficulty::Normal.apply(&config), config);
+}
+
+#[test]
+fn easy_weakens_enemies_and_toughens_the_player() {
+    let base = Config::default();
+    let config = Difficulty::Easy.apply(&base);
+    assert_eq!(config.enemy.start_health, enemy_constants::STARTHEALTH * 3 / 5);
+    assert_eq!(config.enemy.cooldown, base.enemy.cooldown * 1.5);
+    assert_eq!(config.enemy.shooting_error, base.enemy.shooting_error * 2.0);
+    assert_eq!(config.boss.volley_size, boss_constants::TOTBULL * 3 / 5);
+    assert_eq!(config.orb.cooldown, orb_constants::COOLDOWN * 1.5);
+    assert_eq!(config.player.start_health, player_constants::STARTHEALTH * 3 / 2);
+    assert_eq!(config.game.enemy_kill, game_constants::ENEMYKILL * 2);
+    assert_eq!(config.game.boss_kill, game_constants::BOSSKILL * 2);
+}
+
+#[test]
+fn hard_toughens_enemies_and_weakens_the_player() {
+    let base = Config::default();
+    let config = Difficulty::Hard.apply(&base);
+    assert_eq!(config.enemy.start_health, enemy_constants::STARTHEALTH * 7 / 5);
+    assert_eq!(config.enemy.cooldown, base.enemy.cooldown * 0.8);
+    assert_eq!(config.enemy.shooting_error, base.enemy.shooting_error * 0.6);
+    assert_eq!(config.boss.volley_size, boss_constants::TOTBULL * 7 / 5);
+    assert_eq!(config.orb.cooldown, orb_constants::COOLDOWN * 0.75);
+    assert_eq!(config.player.start_health, player_constants::STARTHEALTH * 4 / 5);
+    assert_eq!(config.game.enemy_kill, game_constants::ENEMYKILL);
+    assert_eq!(config.game.boss_kill, game_constants::BOSSKILL);
+}
+
+#[test]
+fn nightmare_also_halves_healing() {
+    let base = Config::default();
+    let config = Difficulty::Nightmare.apply(&base);
+    assert_eq!(config.enemy.start_health, enemy_constants::STARTHEALTH * 2);
+    assert_eq!(config.enemy.cooldown, base.enemy.cooldown * 0.6);
+    assert_eq!(config.boss.volley_size, boss_constants::TOTBULL * 2);
+    assert_eq!(config.orb.cooldown, orb_constants::COOLDOWN * 0.5);
+    assert_eq!(config.player.start_health, player_constants::STARTHEALTH / 2);
+    assert_eq!(config.game.enemy_kill, game_constants::ENEMYKILL / 2);
+    assert_eq!(config.game.boss_kill, game_constants::BOSSKILL / 2);
+}
+
+#[test]
+fn healths_and_volleys_stay_at_least_one() {
+    let mut base = Config::default();
+    base.enemy.start_health = 1;
+    base.boss.volley_size = 1;
+    base.player.start_health = 1;
+    let config = Difficulty::Easy.apply(&base);
+    assert_eq!(config.enemy.start_health, 1);
+    assert_eq!(config.boss.volley_size, 1);
+
+    let config = Difficulty::Nightmare.apply(&base);
+    assert_eq!(config.player.start_health, 1);
+
+    base.boss.volley_size = 0;
+    assert_eq!(Difficulty::Nightmare.apply(&base).boss.volley_size, 0);
+}
@@ -0,0 +1,111 @@
+extern crate hit_n_run;
+
+use hit_n_run::config::{Config, DirectorConfig};
+use hit_n_run::director::Director;
+use hit_n_run::events::{DamageSource, Event};
+use hit_n_run::vector::Vector;
+
+/// Returns an enabled director config with round numbers
+fn config() -> DirectorConfig {
+    DirectorConfig {
+        enabled: true,
+        interval: 1.0,
+        window: 3.0,
+        step: 0.5,
+        damage_rate: 1.0,
+        kill_rate: 1.0,
+        low_health: 0.25,
+        spawn_range: 2,
+        fire_range: 0.5,
+        boss_range: 1,
+    }
+}
+
+/// Plays a sample of one second with `events` at `health`
+fn sample(director: &mut Director, events: &[Event], health: f64) -> Option<f64> {
+    for event in events {
+        director.record(event);
+    }
+    director.update(1.0, health, &config())
+}
+
+/// Returns `count` copies of an event
+fn times(event: Event, count: usize) -> Vec<Event> {
+    vec![event; count]
+}
+
+#[test]
+fn many_kills_make_it_harder_up_to_the_top() {
+    let mut director = Director::new();
+    let kills = times(Event::EnemyKilled { pos: Vector::new(0.0, 0.0) }, 3);
+    assert_eq!(director.update(0.5, 1.0, &config()), None);
+    assert_eq!(director.update(0.5, 1.0, &config()), None);
+    assert_eq!(sample(&mut director, &kills, 1.0), Some(0.5));
+    assert_eq!(sample(&mut director, &kills, 1.0), Some(1.0));
+    assert_eq!(sample(&mut director, &kills, 1.0), None);
+    assert_eq!(director.get_rating(), 1.0);
+
+    assert_eq!(director.get_reinforcements(3, &config()), 5);
+    assert_eq!(director.get_bosses(1, &config()), 2);
+    assert_eq!(director.get_bosses(0, &config()), 0);
+    assert_eq!(director.get_cooldown_scale(&config()), 0.5);
+}
+
+#[test]
+fn much_damage_makes_it_easier_down_to_the_bottom() {
+    let mut director = Director::new();
+    let hits = times(Event::PlayerDamaged { source: DamageSource::Bullet }, 3);
+    assert_eq!(sample(&mut director, &hits, 1.0), Some(-0.5));
+    assert_eq!(sample(&mut director, &hits, 1.0), Some(-1.0));
+    assert_eq!(sample(&mut director, &hits, 1.0), None);
+    assert_eq!(director.get_rating(), -1.0);
+
+    assert_eq!(director.get_reinforcements(3, &config()), 1);
+    assert_eq!(director.get_reinforcements(1, &config()), 0);
+    assert_eq!(director.get_bosses(1, &config()), 1);
+    assert_eq!(director.get_cooldown_scale(&config()), 1.5);
+}
+
+#[test]
+fn low_health_makes_it_easier_despite_kills() {
+    let mut director = Director::new();
+    let kills = times(Event::BossKilled { pos: Vector::new(0.0, 0.0) }, 3);
+    assert_eq!(sample(&mut director, &kills, 0.1), Some(-0.5));
+    assert_eq!(sample(&mut director, &[], 1.0), Some(0.0));
+}
+
+#[test]
+fn rates_only_count_the_window() {
+    let mut director = Director::new();
+    let hits = times(Event::PlayerDamaged { source: DamageSource::Orb }, 4);
+    assert_eq!(sample(&mut director, &hits, 1.0), Some(-0.5));
+    // 4 hits in the last 2 seconds are still more than 1 a second
+    assert_eq!(sample(&mut director, &[], 1.0), Some(-1.0));
+    assert_eq!(sample(&mut director, &[], 1.0), None);
+
+    // the hits have left the window, and kills add up over it
+    let kills = times(Event::EnemyKilled { pos: Vector::new(0.0, 0.0) }, 2);
+    assert_eq!(sample(&mut director, &kills, 1.0), None);
+    assert_eq!(sample(&mut director, &kills, 1.0), Some(-0.5));
+}
+
+#[test]
+fn disabled_director_goes_back_to_the_config() {
+    let mut director = Director::new();
+    let kills = times(Event::EnemyKilled { pos: Vector::new(0.0, 0.0) }, 3);
+    sample(&mut director, &kills, 1.0);
+
+    let game = Config {
+        director: config(),
+        ..Config::default()
+    };
+    let harder = director.apply(&game);
+    assert_eq!(harder.enemy.cooldown, game.enemy.cooldown * 0.75);
+    assert_eq!(harder.boss.cooldown, game.boss.cooldown * 0.75);
+
+    let disabled = DirectorConfig { enabled: false, ..config() };
+    assert_eq!(director.update(1.0, 1.0, &disabled), Some(0.0));
+    assert_eq!(director.update(1.0, 1.0, &disabled), None);
+    assert_eq!(director, Director::new());
+    assert_eq!(director.apply(&game), game);
+}
@@ -0,0 +1,55 @@
+extern crate hit_n_run;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use hit_n_run::config::difficulty::Difficulty;
+use hit_n_run::highscores::{Entry, HighScores};
+
+/// Returns a path for a table in the temporary directory
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("hit_n_run-test-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn saved_table_loads_the_same() {
+    let path = temp_path("highscores");
+    let mut scores = HighScores::new();
+    scores.insert(Entry::new("ann", 30, 3, 61.5, 7, Difficulty::Hard));
+    scores.insert(Entry::new("bob smith", 12, 2, 20.0, 8, Difficulty::Normal));
+    scores.save(&path).unwrap();
+
+    let (loaded, skipped) = HighScores::load(&path).unwrap();
+    assert_eq!(skipped, 0);
+    assert_eq!(loaded.get_entries(), scores.get_entries());
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn bytes_that_arent_text_only_cost_their_lines() {
+    let path = temp_path("highscores-binary");
+    let mut scores = HighScores::new();
+    scores.insert(Entry::new("ann", 30, 3, 61.5, 7, Difficulty::Hard));
+    scores.insert(Entry::new("bob", 12, 2, 20.0, 8, Difficulty::Normal));
+    scores.save(&path).unwrap();
+
+    // a broken score and a broken name
+    let mut bytes = fs::read(&path).unwrap();
+    bytes.extend_from_slice(b"4\xff0 1 2.0 3 2017-05-04 normal eve\n");
+    bytes.extend_from_slice(b"5 1 2.0 3 2017-05-04 normal \xc3\x28ve\n");
+    fs::write(&path, &bytes).unwrap();
+
+    let (loaded, skipped) = HighScores::load(&path).unwrap();
+    assert_eq!(skipped, 1);
+    let names: Vec<&str> = loaded.get_entries().iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, vec!["ann", "bob", "\u{fffd}(ve"]);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn missing_table_is_empty() {
+    let (scores, skipped) = HighScores::load(temp_path("highscores-missing")).unwrap();
+    assert!(scores.get_entries().is_empty());
+    assert_eq!(skipped, 0);
+}
@@ -0,0 +1,117 @@
+extern crate hit_n_run;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use hit_n_run::config::Config;
+use hit_n_run::config::difficulty::Difficulty;
+use hit_n_run::replay::{Recorder, Replay};
+use hit_n_run::script::Script;
+use hit_n_run::simulation::Input;
+
+/// Returns a path for a replay in the temporary directory
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("hit_n_run-test-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn recorded_inputs_are_on_disk_before_the_game_ends() {
+    let path = temp_path("crash");
+    let mut recorder = Recorder::new(path.clone(), 42, 800.0, 600.0);
+    recorder.set_difficulty(Difficulty::Hard);
+    recorder.record(0, &[Input::Target(10.0, 20.5), Input::Shoot(true)]).unwrap();
+    recorder.record(1, &[]).unwrap();
+    recorder.record(2, &[Input::Dash, Input::Move(0.6, -0.8)]).unwrap();
+
+    // read it back while the recorder is still alive, as after a crash
+    let replay = Replay::load(&path).unwrap();
+    assert_eq!(replay.seed, 42);
+    assert_eq!(replay.dimensions, [800.0, 600.0]);
+    assert_eq!(replay.difficulty, Difficulty::Hard);
+    assert_eq!(replay.inputs,
+               vec![(0, Input::Target(10.0, 20.5)),
+                    (0, Input::Shoot(true)),
+                    (2, Input::Dash),
+                    (2, Input::Move(0.6, -0.8))]);
+
+    drop(recorder);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn finishing_without_steps_writes_an_empty_replay() {
+    let path = temp_path("empty");
+    let mut recorder = Recorder::new(path.clone(), 7, 640.0, 480.0);
+    recorder.finish().unwrap();
+
+    let replay = Replay::load(&path).unwrap();
+    assert_eq!(replay, Replay::new(7, 640.0, 480.0));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn written_replay_reads_back_the_same() {
+    let mut replay = Replay::new(3, 800.0, 600.0);
+    replay.difficulty = Difficulty::Nightmare;
+    replay.record(0, &[Input::Aim(1.0 / 3.0, 2.0), Input::Shoot(false)]);
+    replay.record(5, &[Input::Reset, Input::Resize(1024.0, 768.0)]);
+
+    let mut text = Vec::new();
+    replay.write(&mut text).unwrap();
+    assert_eq!(Replay::read(&text[..]).unwrap(), replay);
+}
+
+#[test]
+fn config_and_levels_are_checked() {
+    let (config, script) = (Config::default(), Script::default());
+    let mut replay = Replay::new(3, 800.0, 600.0);
+    assert!(replay.get_mismatches(&config, &script).is_empty());
+
+    replay.set_sources(&config, &script);
+    let mut text = Vec::new();
+    replay.write(&mut text).unwrap();
+    let replay = Replay::read(&text[..]).unwrap();
+    assert!(replay.config_hash.is_some());
+    assert!(replay.get_mismatches(&config, &script).is_empty());
+
+    let mut faster = config.clone();
+    faster.player.velocity += 1.0;
+    assert_eq!(replay.get_mismatches(&faster, &script), vec!["config"]);
+
+    let mut shorter = script.clone();
+    shorter.levels[0].kill_target += 1;
+    assert_eq!(replay.get_mismatches(&faster, &shorter), vec!["config", "levels"]);
+}
+
+#[test]
+fn bad_hash_is_an_error() {
+    let text = "hit_n_run replay 2\nseed 1\nsize 800 600\nconfig nothex\n";
+    assert!(Replay::read(text.as_bytes()).is_err());
+}
+
+#[test]
+fn replays_are_written_as_version_2() {
+    let mut out = Vec::new();
+    Replay::new(1, 800.0, 600.0).write(&mut out).unwrap();
+    assert!(String::from_utf8(out).unwrap().starts_with("hit_n_run replay 2\n"));
+}
+
+#[test]
+fn version_1_replays_still_read() {
+    let text = "hit_n_run replay 1\nseed 9\nsize 640 480\n0 target 1 2\n3 shoot true\n5 reset\n";
+    let replay = Replay::read(text.as_bytes()).unwrap();
+    assert_eq!(replay.seed, 9);
+    assert_eq!(replay.difficulty, Difficulty::Normal);
+    assert_eq!(replay.config_hash, None);
+    assert_eq!(replay.inputs,
+               vec![(0, Input::Target(1.0, 2.0)), (3, Input::Shoot(true)), (5, Input::Reset)]);
+}
+
+#[test]
+fn unknown_versions_are_refused() {
+    for header in &["hit_n_run replay 3", "hit_n_run replay 0", "hit_n_run replay", "replay 1"] {
+        let text = format!("{}\nseed 1\nsize 800 600\n", header);
+        assert!(Replay::read(text.as_bytes()).is_err(), "{} was read", header);
+    }
+}
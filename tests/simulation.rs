@@ -0,0 +1,28 @@
+extern crate hit_n_run;
+
+use hit_n_run::config::Config;
+use hit_n_run::constants::game_constants::TIMESTEP;
+use hit_n_run::script::Script;
+use hit_n_run::simulation::{Input, Simulation};
+
+#[test]
+fn screen_without_size_still_spawns() {
+    let mut sim = Simulation::new(0.0, 0.0, 1, Config::default(), Script::default());
+    assert_eq!(sim.get_dimensions(), [1.0, 1.0]);
+    sim.step(TIMESTEP, &[]);
+    assert!(!sim.get_enemies().is_empty());
+}
+
+#[test]
+fn minimized_window_still_spawns() {
+    let mut sim = Simulation::new(800.0, 600.0, 1, Config::default(), Script::default());
+    sim.step(TIMESTEP, &[Input::Resize(0.0, 0.0)]);
+    assert_eq!(sim.get_dimensions(), [1.0, 1.0]);
+
+    // a reset spawns the first level again on the empty screen
+    sim.step(TIMESTEP, &[Input::Reset]);
+    assert!(!sim.get_enemies().is_empty());
+
+    sim.step(TIMESTEP, &[Input::Resize(1024.0, 768.0)]);
+    assert_eq!(sim.get_dimensions(), [1024.0, 768.0]);
+}
@@ -0,0 +1,156 @@
+extern crate hit_n_run;
+
+use hit_n_run::config::Config;
+use hit_n_run::config::difficulty::Difficulty;
+use hit_n_run::constants::game_constants::TIMESTEP;
+use hit_n_run::controller::Controller;
+use hit_n_run::controller::bot::{Bot, Strategy};
+use hit_n_run::script::Script;
+use hit_n_run::simulation::Simulation;
+use hit_n_run::snapshot::{Reader, Snapshot, SnapshotError, Writer};
+
+/// Returns the snapshot text of a simulation
+fn save(sim: &Simulation) -> String {
+    let mut w = Writer::new();
+    sim.save(&mut w);
+    w.finish()
+}
+
+/// Reads a simulation from snapshot text
+fn load(text: &str) -> Result<Simulation, SnapshotError> {
+    Simulation::load(&mut Reader::new(text))
+}
+
+/// Returns a game that has been played for `ticks` steps by the bot, so it
+/// has enemies, bullets and a boss or two
+fn played(ticks: u64) -> Simulation {
+    let mut sim = Simulation::new(800.0, 600.0, 11, Config::default(), Script::default());
+    let mut bot = Bot::new(Strategy::Hunter);
+    let mut input = Vec::new();
+    for _ in 0..ticks {
+        input.clear();
+        bot.inputs(&sim, &mut input);
+        sim.step(TIMESTEP, &input);
+    }
+    sim
+}
+
+/// Plays a game on with the bot
+fn play_on(sim: &mut Simulation, ticks: u64) {
+    let mut bot = Bot::new(Strategy::Hunter);
+    let mut input = Vec::new();
+    for _ in 0..ticks {
+        input.clear();
+        bot.inputs(sim, &mut input);
+        sim.step(TIMESTEP, &input);
+    }
+}
+
+#[test]
+fn round_trip_gives_the_same_text() {
+    let sim = played(3000);
+    let text = save(&sim);
+    let loaded = load(&text).unwrap();
+    assert_eq!(save(&loaded), text);
+}
+
+#[test]
+fn loaded_game_plays_on_the_same() {
+    let mut sim = played(2000);
+    let mut loaded = load(&save(&sim)).unwrap();
+    play_on(&mut sim, 2000);
+    play_on(&mut loaded, 2000);
+    assert_eq!(save(&loaded), save(&sim));
+}
+
+/// Returns snapshot text with the line starting with `tag` replaced by `line`
+fn with_line(text: &str, tag: &str, line: &str) -> String {
+    let start = text.find(&format!("\n{} ", tag)).expect("tag is in the snapshot") + 1;
+    let end = start + text[start..].find('\n').unwrap_or(text.len() - start);
+    format!("{}{}{}", &text[..start], line, &text[end..])
+}
+
+#[test]
+fn huge_list_length_is_an_error() {
+    let text = save(&played(600));
+    for tag in &["spawned", "bosses"] {
+        match load(&with_line(&text, tag, &format!("{} {}", tag, usize::MAX))) {
+            Err(SnapshotError::Malformed(_)) => {}
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("corrupt snapshot of {} loaded", tag),
+        }
+    }
+}
+
+#[test]
+fn spawn_counts_fit_the_level_after_loading() {
+    // a game saved with other levels can have fewer spawns than the default
+    let text = with_line(&save(&played(600)), "spawned", "spawned 0");
+    let mut sim = load(&text).unwrap();
+    play_on(&mut sim, 600);
+    assert!(sim.get_tick() > 600);
+}
+
+#[test]
+fn truncated_snapshot_is_an_error() {
+    let text = save(&played(600));
+    // a cut inside the last word could still leave a valid number
+    let last_word = text.trim_end().rfind(char::is_whitespace).unwrap();
+    for len in (0..last_word).step_by(97) {
+        if let Some(cut) = text.get(..len) {
+            assert!(load(cut).is_err(), "cut at {} loaded", len);
+        }
+    }
+}
+
+#[test]
+fn difficulty_is_saved() {
+    let config = Difficulty::Hard.apply(&Config::default());
+    let mut sim = Simulation::new(800.0, 600.0, 3, config.clone(), Script::default());
+    sim.set_difficulty(Difficulty::Hard);
+    play_on(&mut sim, 300);
+
+    let loaded = load(&save(&sim)).unwrap();
+    assert_eq!(loaded.get_difficulty(), Difficulty::Hard);
+    assert_eq!(loaded.get_config(), &config);
+
+    let text = save(&sim).replace("\ndifficulty hard\n", "\ndifficulty impossible\n");
+    assert!(load(&text).is_err());
+}
+
+#[test]
+fn numbers_have_to_be_finite() {
+    let text = save(&played(600));
+    let game = text.lines().find(|l| l.starts_with("game ")).unwrap();
+    assert_eq!(game.split(' ').count(), 14);
+    for bad in &["inf", "-inf", "NaN", "1e999"] {
+        // the time into the level is the last value of the line
+        let mut words: Vec<&str> = game.split(' ').collect();
+        words[13] = bad;
+        let corrupt = text.replace(game, &words.join(" "));
+        assert!(load(&corrupt).is_err(), "{} was loaded", bad);
+
+        // and the x of the player
+        let player = text.lines().find(|l| l.starts_with("player ")).unwrap();
+        let mut words: Vec<&str> = player.split(' ').collect();
+        words[1] = bad;
+        assert!(load(&text.replace(player, &words.join(" "))).is_err(), "{} was loaded", bad);
+    }
+}
+
+#[test]
+fn screen_has_to_have_a_size() {
+    let text = save(&played(60));
+    let game = text.lines().find(|l| l.starts_with("game ")).unwrap();
+    for size in &["0 600", "800 0", "-800 600"] {
+        let mut words: Vec<&str> = game.split(' ').collect();
+        let size: Vec<&str> = size.split(' ').collect();
+        words[1] = size[0];
+        words[2] = size[1];
+        match load(&text.replace(game, &words.join(" "))) {
+            Err(SnapshotError::Malformed(_)) => {}
+            Err(e) => panic!("unexpected error {}", e),
+            Ok(_) => panic!("screen of {} {} loaded", size[0], size[1]),
+        }
+    }
+}
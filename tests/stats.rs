@@ -0,0 +1,92 @@
+extern crate hit_n_run;
+
+use hit_n_run::config::Config;
+use hit_n_run::constants::game_constants::TIMESTEP;
+use hit_n_run::constants::player_constants::STARTSHOTS;
+use hit_n_run::events::{DamageSource, Event};
+use hit_n_run::script::Script;
+use hit_n_run::simulation::{Input, Simulation};
+use hit_n_run::stats::RunStats;
+use hit_n_run::vector::Vector;
+
+/// Returns stats that recorded `events`
+fn recorded(events: &[Event], config: &Config) -> RunStats {
+    let mut stats = RunStats::new();
+    for event in events {
+        stats.record(event, config);
+    }
+    stats
+}
+
+#[test]
+fn shots_hits_and_kills_are_counted() {
+    let pos = Vector::new(0.0, 0.0);
+    let mut config = Config::default();
+    config.game.enemy_kill = 3;
+    config.game.boss_kill = 10;
+    let stats = recorded(&[Event::PlayerFired,
+                           Event::PlayerFired,
+                           Event::PlayerFired,
+                           Event::PlayerFired,
+                           Event::EnemyHit { pos },
+                           Event::EnemyKilled { pos },
+                           Event::BossHit { pos },
+                           Event::PlayerDashed],
+                         &config);
+    assert_eq!(stats.shots, 4);
+    assert_eq!(stats.hits, 3);
+    assert_eq!(stats.get_accuracy(), 0.75);
+    assert_eq!(stats.enemies_killed, 1);
+    assert_eq!(stats.bosses_killed, 0);
+    assert_eq!(stats.health_gained, 3);
+
+    let stats = recorded(&[Event::BossKilled { pos }], &config);
+    assert_eq!(stats.bosses_killed, 1);
+    assert_eq!(stats.health_gained, 10);
+    // hits without shots give no accuracy rather than dividing by 0
+    assert_eq!(stats.get_accuracy(), 0.0);
+}
+
+#[test]
+fn damage_is_counted_by_source() {
+    let bullet = Event::PlayerDamaged { source: DamageSource::Bullet };
+    let orb = Event::PlayerDamaged { source: DamageSource::Orb };
+    let stats = recorded(&[bullet, orb, bullet, bullet], &Config::default());
+    assert_eq!(stats.bullet_damage, 3);
+    assert_eq!(stats.orb_damage, 1);
+}
+
+#[test]
+fn time_is_split_by_level_and_cooldown() {
+    let mut stats = RunStats::new();
+    stats.add_time(2.0, false);
+    stats.add_time(0.5, true);
+    stats.record(&Event::LevelAdvanced { level: 2 }, &Config::default());
+    stats.add_time(1.5, true);
+    assert_eq!(stats.level_times, vec![2.5, 1.5]);
+    assert_eq!(stats.get_time(), 4.0);
+    assert_eq!(stats.burst_cooldown_time, 2.0);
+}
+
+#[test]
+fn simulation_counts_a_burst_and_its_cooldown() {
+    let mut sim = Simulation::new(800.0, 600.0, 1, Config::default(), Script::default());
+    let mut fired = 0;
+    let mut steps = 0;
+    while sim.get_stats().burst_cooldown_time == 0.0 {
+        sim.step(TIMESTEP, &[Input::Shoot(true)]);
+        fired += sim.get_events().iter().filter(|e| **e == Event::PlayerFired).count() as u32;
+        steps += 1;
+        assert!(steps < 1000, "the burst never ran out");
+    }
+    assert_eq!(fired, STARTSHOTS);
+    assert_eq!(sim.get_stats().shots, STARTSHOTS);
+
+    for _ in 0..10 {
+        sim.step(TIMESTEP, &[]);
+    }
+    let stats = sim.get_stats();
+    assert!((stats.burst_cooldown_time - 11.0 * TIMESTEP).abs() < 1e-9,
+            "cooled down for {}",
+            stats.burst_cooldown_time);
+}
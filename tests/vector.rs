@@ -0,0 +1,136 @@
+#[macro_use]
+extern crate quickcheck;
+extern crate hit_n_run;
+
+use quickcheck::TestResult;
+use std::f64;
+
+use hit_n_run::vector::Vector;
+
+/// Returns whether two numbers are equal, allowing for rounding errors
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-9 * (1.0 + a.abs().max(b.abs()))
+}
+
+/// Returns whether two vectors are equal, allowing for rounding errors
+fn close_vec(a: Vector, b: Vector) -> bool {
+    close(a.x, b.x) && close(a.y, b.y)
+}
+
+quickcheck! {
+    fn add_commutes(ax: f64, ay: f64, bx: f64, by: f64) -> bool {
+        let (a, b) = (Vector::new(ax, ay), Vector::new(bx, by));
+        a + b == b + a
+    }
+
+    fn sub_undoes_add(ax: f64, ay: f64, bx: f64, by: f64) -> bool {
+        let (a, b) = (Vector::new(ax, ay), Vector::new(bx, by));
+        close_vec(a + b - b, a)
+    }
+
+    fn neg_adds_to_zero(x: f64, y: f64) -> bool {
+        let v = Vector::new(x, y);
+        v + -v == Vector::default()
+    }
+
+    fn div_undoes_mul(x: f64, y: f64, s: f64) -> TestResult {
+        if s == 0.0 {
+            return TestResult::discard();
+        }
+        let v = Vector::new(x, y);
+        TestResult::from_bool(close_vec(v * s / s, v) && v * s == s * v)
+    }
+
+    fn assign_ops_match(ax: f64, ay: f64, bx: f64, by: f64, s: f64) -> bool {
+        let (a, b) = (Vector::new(ax, ay), Vector::new(bx, by));
+        let mut v = a;
+        v += b;
+        v -= a;
+        v *= s;
+        v == (a + b - a) * s
+    }
+
+    fn dot_with_self_is_length_squared(x: f64, y: f64) -> bool {
+        let v = Vector::new(x, y);
+        close(v.dot(&v), v.length().powi(2)) && v.dot(&v) == v.length_squared()
+    }
+
+    fn dot_commutes(ax: f64, ay: f64, bx: f64, by: f64) -> bool {
+        let (a, b) = (Vector::new(ax, ay), Vector::new(bx, by));
+        a.dot(&b) == b.dot(&a)
+    }
+
+    fn cross_anticommutes(ax: f64, ay: f64, bx: f64, by: f64) -> bool {
+        let (a, b) = (Vector::new(ax, ay), Vector::new(bx, by));
+        a.cross(&b) == -b.cross(&a) && a.cross(&a) == 0.0
+    }
+
+    fn normalize_has_length_one(x: f64, y: f64) -> TestResult {
+        let v = Vector::new(x, y);
+        if v.length() == 0.0 {
+            return TestResult::from_bool(v.normalize() == v);
+        }
+        let n = v.normalize();
+        TestResult::from_bool(close(n.length(), 1.0) && close(n.angle(), v.angle()))
+    }
+
+    fn from_angle_round_trips(angle: f64) -> TestResult {
+        if angle.abs() >= f64::consts::PI {
+            return TestResult::discard();
+        }
+        let v = Vector::from_angle(angle);
+        TestResult::from_bool(close(v.length(), 1.0) && close(v.angle(), angle))
+    }
+
+    fn rotate_keeps_length(x: f64, y: f64, angle: f64) -> bool {
+        let v = Vector::new(x, y);
+        close(v.rotate(angle).length(), v.length())
+    }
+
+    fn rotate_back_is_identity(x: f64, y: f64, angle: f64) -> bool {
+        let v = Vector::new(x, y);
+        let back = v.rotate(angle).rotate(-angle);
+        back.dist(&v) <= 1e-9 * (1.0 + v.length())
+    }
+
+    fn rotate_turns_counterclockwise(x: f64, y: f64) -> TestResult {
+        let v = Vector::new(x, y);
+        if v.length() == 0.0 {
+            return TestResult::discard();
+        }
+        let turned = v.rotate(f64::consts::FRAC_PI_2);
+        TestResult::from_bool(close(v.cross(&turned), v.length_squared()))
+    }
+
+    fn lerp_hits_both_ends(ax: f64, ay: f64, bx: f64, by: f64) -> bool {
+        let (a, b) = (Vector::new(ax, ay), Vector::new(bx, by));
+        a.lerp(&b, 0.0) == a && close_vec(a.lerp(&b, 1.0), b)
+    }
+
+    fn lerp_halfway_is_equidistant(ax: f64, ay: f64, bx: f64, by: f64) -> bool {
+        let (a, b) = (Vector::new(ax, ay), Vector::new(bx, by));
+        let mid = a.lerp(&b, 0.5);
+        close(mid.dist(&a), mid.dist(&b))
+    }
+
+    fn dist_is_a_metric(ax: f64, ay: f64, bx: f64, by: f64, cx: f64, cy: f64) -> bool {
+        let (a, b, c) = (Vector::new(ax, ay), Vector::new(bx, by), Vector::new(cx, cy));
+        a.dist(&b) == b.dist(&a) && a.dist(&a) == 0.0 &&
+        a.dist(&c) <= (a.dist(&b) + b.dist(&c)) * (1.0 + 1e-12)
+    }
+
+    fn clamp_to_rect_stays_inside(x: f64, y: f64, ax: f64, ay: f64, bx: f64, by: f64) -> bool {
+        let min = Vector::new(ax.min(bx), ay.min(by));
+        let max = Vector::new(ax.max(bx), ay.max(by));
+        let c = Vector::new(x, y).clamp_to_rect(&min, &max);
+        min.x <= c.x && c.x <= max.x && min.y <= c.y && c.y <= max.y &&
+        c.clamp_to_rect(&min, &max) == c
+    }
+
+    fn clamp_to_rect_keeps_points_inside(x: f64, y: f64, w: f64, h: f64) -> bool {
+        let v = Vector::new(x, y);
+        let min = v - Vector::new(w.abs(), h.abs());
+        let max = v + Vector::new(w.abs(), h.abs());
+        v.clamp_to_rect(&min, &max) == v
+    }
+}